@@ -1,8 +1,16 @@
+use verglas::forge::{Options, VerticalMetrics};
+
 fn main() {
     println!("cargo::rerun-if-changed=assets/icons/*");
 
     let source_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/icons");
     let font_file_dest = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/example-icons.ttf");
 
-    verglas::make_font(source_dir, font_file_dest).expect("building icon font failed")
+    // Align icons with the text next to them
+    let options = Options {
+        vertical_metrics: VerticalMetrics::text_aligned(),
+        ..Options::default()
+    };
+    verglas::make_font_with_options(source_dir, font_file_dest, &options)
+        .expect("building icon font failed");
 }
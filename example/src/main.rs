@@ -20,7 +20,7 @@ struct App;
 impl App {
     pub fn update(&mut self, _message: ()) {}
 
-    pub fn view(&self) -> Element<()> {
+    pub fn view(&self) -> Element<'_, ()> {
         center(
             container(
                 column![
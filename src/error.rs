@@ -21,12 +21,33 @@ pub enum Error {
     #[error("SVG parsing error: {0}")]
     SvgParse(#[from] usvg::Error),
 
+    #[error("invalid icon manifest: {0}")]
+    InvalidManifest(String),
+
     #[error("failed to create font: {0}")]
     FontCreation(String),
 
     #[error("failed to create glyph: {0}")]
     GlyphConversion(String),
 
+    #[error("{0} glyphs exceed the maximum of 65535 glyphs per font")]
+    TooManyGlyphs(usize),
+
+    #[error("{0} icons exceed the {1} available codepoints")]
+    CodepointsExhausted(usize, usize),
+
+    #[error("codepoint U+{0:04X} is assigned to both {1} and {2}")]
+    CodepointConflict(u32, String, String),
+
+    #[error("value {0} exceeds the range of font units")]
+    FontUnitsOverflow(f64),
+
+    #[error("scale {0} of adjustment is not a positive number")]
+    InvalidScale(f64),
+
+    #[error("composed icon {0} requires the missing icon {1}")]
+    MissingIcon(String, String),
+
     #[cfg(feature = "forge")]
     #[error("failed to build font table: {0}")]
     BuildFontTable(#[from] BuilderError),
@@ -3,57 +3,303 @@
 //! Also provides access to lower-level functions for more fine-grained control.
 
 use std::{
-    fs,
+    collections::{HashMap, HashSet},
+    fs, iter,
     path::{Path, PathBuf},
+    sync::Arc,
 };
-use write_fonts::tables::glyf::SimpleGlyph;
 
-pub use self::{file::collect_svg_paths, font::build_font};
+pub use self::{
+    adjustment::{Adjustment, Fit},
+    codepoint::{
+        BaselineAllocator, CodepointAllocator, ExplicitAllocator, HashAllocator,
+        NamespaceAllocator, SequentialAllocator, SortedAllocator,
+    },
+    color::{Color, ColorFormat, ColorStop, Extend, Gradient, GradientKind, Paint},
+    compose::{ComposedIcon, Corner},
+    file::collect_svg_paths,
+    font::{build_font, ColorLayer, IconGlyph},
+    manifest::{Manifest, ManifestIcon},
+    metrics::VerticalMetrics,
+    options::{AdvanceWidth, Options},
+    report::{PointSavings, Report},
+    variant::{Variant, VariantTransform},
+};
 
+mod adjustment;
+mod codepoint;
+mod color;
+mod compose;
 mod file;
 mod font;
+mod manifest;
+mod metrics;
+mod options;
+mod outline;
+mod report;
 mod svg;
+mod variant;
+
+use kurbo::BezPath;
 
 use self::{
+    color::color_layers,
     file::{get_font_name, read_svg_file},
-    font::create_glyph,
+    font::{create_color_layer, create_glyph, find_duplicates, scale_factor},
+    manifest::merge_svg_files,
+    outline::{remove_overlaps, simplify_outline},
+    svg::{bounding_box, SvgIcon},
+};
+use crate::{
+    error::Error,
+    index::{build_icon_map, IconMap},
 };
-use crate::error::Error;
 
 /// Builds a TrueType font from SVG icons in a source directory and writes it to a `.ttf` file.
 ///
 /// The font name is derived from the file name that must be included in the destination path.
 /// Glyph names are derived from the name of the SVG file (without file extension) and, when
 /// present, prefixed by subdirectory names separated by "/".
+///
+/// A `manifest.toml` or `manifest.json` file in the source directory describes the icons, see
+/// [`Manifest`]. Use [`Options::manifest`] with [`make_font_with_options`] for manifests in other
+/// places.
 pub fn make_font(
     source_dir: impl AsRef<Path>,
     font_file_dest: impl AsRef<Path>,
 ) -> Result<(), Error> {
+    let source_dir = source_dir.as_ref();
+    let options = Options {
+        manifest: Manifest::find(source_dir),
+        ..Options::default()
+    };
+
+    make_font_with_options(source_dir, font_file_dest, &options).map(|_| ())
+}
+
+/// Builds a TrueType font like [`make_font`] but allows to customize the conversion with [`Options`].
+///
+/// Returns a [`Report`] with details about the conversion.
+pub fn make_font_with_options(
+    source_dir: impl AsRef<Path>,
+    font_file_dest: impl AsRef<Path>,
+    options: &Options,
+) -> Result<Report, Error> {
     let font_name = get_font_name(font_file_dest.as_ref())?;
-    let named_svg_files = collect_svg_paths(source_dir.as_ref())?;
-    let named_glyphs = process_svg_files(named_svg_files)?;
+    let source_dir = source_dir.as_ref();
+    let manifest = options
+        .manifest
+        .as_ref()
+        .map(Manifest::from_file)
+        .transpose()?;
+
+    let mut options = options.clone();
+    let named_svg_files = match manifest {
+        Some(ref manifest) => {
+            // All icons may be described by the manifest
+            let scanned_files = match collect_svg_paths(source_dir) {
+                Err(Error::NoIconsFound) => vec![],
+                scanned_files => scanned_files?,
+            };
+            manifest.apply_overrides(&mut options);
+            merge_svg_files(manifest, scanned_files)
+        }
+        None => collect_svg_paths(source_dir)?,
+    };
+    if named_svg_files.is_empty() {
+        return Err(Error::NoIconsFound);
+    }
+
+    let mut report = Report::default();
+    if let Some(ref manifest) = manifest {
+        report.tags = manifest.tags();
+    }
+    let named_glyphs = process_svg_files(named_svg_files, &options, &mut report)?;
+    report.merged_duplicates = merged_duplicates(&named_glyphs);
 
-    let mut font = build_font(named_glyphs, &font_name)?;
+    if let Some(ref baseline_font) = options.baseline_font {
+        let baseline = build_icon_map(baseline_font)?;
+        report.removed_icons = removed_icons(&baseline, &named_glyphs);
+        options.codepoint_allocator = Arc::new(BaselineAllocator {
+            baseline,
+            allocator: options.codepoint_allocator.clone(),
+        });
+    }
+    // Fixed codepoints of the manifest take precedence over the baseline
+    if let Some(codepoints) = manifest.map(|manifest| manifest.codepoints()) {
+        if !codepoints.is_empty() {
+            options.codepoint_allocator = Arc::new(BaselineAllocator {
+                baseline: codepoints,
+                allocator: options.codepoint_allocator.clone(),
+            });
+        }
+    }
+
+    let mut font = build_font(named_glyphs, &font_name, &options)?;
     fs::write(font_file_dest, font.build())?;
 
-    Ok(())
+    Ok(report)
+}
+
+/// Groups the IDs of icons with identical glyphs, see [`Report::merged_duplicates`].
+fn merged_duplicates(named_glyphs: &[(String, IconGlyph)]) -> Vec<(String, Vec<String>)> {
+    let duplicates = find_duplicates(named_glyphs.iter().map(|(_, icon_glyph)| icon_glyph));
+
+    let mut groups: Vec<(String, Vec<String>)> = vec![];
+    let mut group_indices = HashMap::new();
+    for (index, (icon_id, _)) in named_glyphs.iter().enumerate() {
+        let first = duplicates[index];
+        if first == index {
+            continue;
+        }
+        let group_index = *group_indices.entry(first).or_insert_with(|| {
+            groups.push((named_glyphs[first].0.clone(), vec![]));
+            groups.len() - 1
+        });
+        groups[group_index].1.push(icon_id.clone());
+    }
+
+    groups
+}
+
+/// Returns the sorted IDs of icons in the baseline that are missing from the named glyphs.
+fn removed_icons(baseline: &IconMap, named_glyphs: &[(String, IconGlyph)]) -> Vec<String> {
+    let icon_ids: HashSet<&str> = named_glyphs.iter().map(|(name, _)| name.as_str()).collect();
+    let mut removed_icons: Vec<String> = baseline
+        .keys()
+        .filter(|icon_id| *icon_id != ".notdef" && !icon_ids.contains(icon_id.as_str()))
+        .cloned()
+        .collect();
+    removed_icons.sort();
+
+    removed_icons
 }
 
 /// Creates a list of named glyphs.
+///
+/// Variants of an icon follow the icon and composed icons follow all other icons. Files that
+/// cannot be read or parsed are skipped, composed icons whose parts are missing are an error.
 pub fn process_svg_files(
     files: Vec<(String, PathBuf)>,
-) -> Result<Vec<(String, SimpleGlyph)>, Error> {
-    files
-        .into_iter()
-        .filter_map(|(icon_id, file_path)| {
-            match read_svg_file(&file_path)
-                .map(|svg| create_glyph(svg).map(|glyph| (icon_id, glyph)))
-                .transpose()
-            {
-                Ok(Some(glyph)) => Some(Ok(glyph)),
-                Ok(None) => None,
-                Err(e) => Some(Err(e)),
+    options: &Options,
+    report: &mut Report,
+) -> Result<Vec<(String, IconGlyph)>, Error> {
+    let mut named_glyphs = Vec::with_capacity(files.len() + options.variants.len());
+
+    // Icons that are part of composed icons are kept until all files are read
+    let mut parts: HashMap<String, SvgIcon> = HashMap::new();
+    let is_part = |icon_id: &str| {
+        options
+            .composed_icons
+            .iter()
+            .any(|composed| composed.base_id == icon_id || composed.badge_id == icon_id)
+    };
+
+    for (icon_id, file_path) in files {
+        let Some(icon) = read_svg_file(&file_path, &icon_options(&icon_id, options)) else {
+            continue;
+        };
+
+        let variants: Vec<(String, SvgIcon)> = options
+            .variants
+            .iter()
+            .filter(|variant| variant.source_id == icon_id)
+            .map(|variant| (variant.id.clone(), variant.transform.apply(&icon)))
+            .collect();
+        for (icon_id, icon) in iter::once((icon_id, icon)).chain(variants) {
+            if is_part(&icon_id) {
+                parts.insert(icon_id.clone(), icon.clone());
             }
-        })
-        .collect()
+            let icon_glyph =
+                process_icon(&icon_id, icon, &icon_options(&icon_id, options), report)?;
+            named_glyphs.push((icon_id, icon_glyph));
+        }
+    }
+
+    for composed_icon in &options.composed_icons {
+        let part = |icon_id: &String| {
+            parts
+                .get(icon_id)
+                .ok_or_else(|| Error::MissingIcon(composed_icon.id.clone(), icon_id.clone()))
+        };
+        let base = part(&composed_icon.base_id)?;
+        let badge = part(&composed_icon.badge_id)?;
+        let icon_options = icon_options(&composed_icon.id, options);
+        // Tolerances are given in font units
+        let scale_factor =
+            scale_factor(bounding_box(&base.bez_paths), base.view_box, &icon_options);
+        let icon = composed_icon.apply(base, badge, icon_options.curve_tolerance / scale_factor);
+        let icon_glyph = process_icon(&composed_icon.id, icon, &icon_options, report)?;
+        named_glyphs.push((composed_icon.id.clone(), icon_glyph));
+    }
+
+    Ok(named_glyphs)
+}
+
+/// Returns the options with the advance width mode and the adjustment for the given icon.
+fn icon_options(icon_id: &str, options: &Options) -> Options {
+    Options {
+        advance_width: options.advance_width_for(icon_id),
+        adjustment: options.adjustment_for(icon_id),
+        ..options.clone()
+    }
+}
+
+/// Creates a glyph from the outlines of an icon.
+///
+/// Color layers are added if a color format is set and the icon is painted in other colors
+/// than the foreground color.
+fn process_icon(
+    icon_id: &str,
+    icon: SvgIcon,
+    options: &Options,
+    report: &mut Report,
+) -> Result<IconGlyph, Error> {
+    let SvgIcon {
+        bez_paths,
+        paints,
+        view_box,
+    } = icon;
+    // Tolerances are given in font units
+    let scale_factor = scale_factor(bounding_box(&bez_paths), view_box, options);
+    let color_layers = match options.color_format {
+        Some(_) => color_layers(&bez_paths, &paints),
+        None => vec![],
+    };
+
+    let mut bez_paths = prepare_outlines(bez_paths, scale_factor, options);
+    if let Some(tolerance) = options.simplify_tolerance {
+        let simplified = simplify_outline(bez_paths.clone(), tolerance / scale_factor);
+        report
+            .point_savings
+            .push(PointSavings::new(icon_id, &bez_paths, &simplified));
+        bez_paths = simplified;
+    }
+    let mut icon_glyph = create_glyph(bez_paths.clone(), view_box, options)?;
+
+    // Layers are placed like the monochrome glyph, so that both are interchangeable
+    for (paint, layer_paths) in color_layers {
+        let mut layer_paths = prepare_outlines(layer_paths, scale_factor, options);
+        if let Some(tolerance) = options.simplify_tolerance {
+            layer_paths = simplify_outline(layer_paths, tolerance / scale_factor);
+        }
+        icon_glyph.layers.push(create_color_layer(
+            layer_paths,
+            paint,
+            &bez_paths,
+            view_box,
+            options,
+        )?);
+    }
+
+    Ok(icon_glyph)
+}
+
+/// Removes overlaps from the outlines if enabled in the options.
+fn prepare_outlines(bez_paths: Vec<BezPath>, scale_factor: f64, options: &Options) -> Vec<BezPath> {
+    if options.remove_overlaps {
+        remove_overlaps(bez_paths, options.curve_tolerance / scale_factor)
+    } else {
+        bez_paths
+    }
 }
@@ -0,0 +1,53 @@
+/// Manual adjustments of the size and position of an icon within its glyph.
+///
+/// Lengths are given as fractions of the em, so that adjustments do not depend on the units
+/// per em. Adjustments are applied before points are rounded to font units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Adjustment {
+    /// Space between the icon and the edges of the icon area on each side.
+    ///
+    /// Negative values let the icon extend beyond the icon area.
+    pub padding: f64,
+    /// Factor by which the icon is scaled around its center after it has been fitted.
+    ///
+    /// Must be positive, otherwise creating the glyph fails. The advance width is not affected.
+    pub scale: f64,
+    /// Horizontal and vertical shift of the icon, positive values move it right and up.
+    ///
+    /// The advance width is not affected.
+    pub offset: (f64, f64),
+    /// How the icon is fitted into the icon area.
+    pub fit: Fit,
+}
+
+/// Determines which area of an icon is fitted into the icon area.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Fit {
+    /// The outlines are scaled to fit into the icon area as a whole.
+    #[default]
+    Contain,
+    /// The SVG view box is scaled to fit into the icon area, which preserves the space around
+    /// the icon as drawn.
+    ViewBox,
+    /// The outlines are scaled to cover the icon area and may extend beyond it.
+    ///
+    /// Only differs from [`Fit::Contain`] for [`AdvanceWidth::Square`](super::AdvanceWidth::Square),
+    /// otherwise the width of the icon area follows the icon.
+    Cover,
+}
+
+impl Default for Adjustment {
+    fn default() -> Self {
+        Self {
+            padding: 0.0,
+            scale: 1.0,
+            offset: (0.0, 0.0),
+            fit: Fit::default(),
+        }
+    }
+}
+
+/// Returns `true` for valid scale factors, which are positive and finite.
+pub(crate) fn is_positive(value: f64) -> bool {
+    value > 0.0 && value.is_finite()
+}
@@ -0,0 +1,395 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    ops::RangeInclusive,
+    sync::Arc,
+};
+
+use super::options::matches_pattern;
+use crate::{
+    index::{IconMap, PRIVATE_USE_AREAS},
+    Error,
+};
+
+/// Assigns codepoints to icons.
+///
+/// Implementations return distinct codepoints, which are usually taken from the private use
+/// areas within the codepoint range of the [`Options`](super::Options).
+pub trait CodepointAllocator: fmt::Debug + Send + Sync {
+    /// Returns a codepoint for each icon ID in the same order as the IDs.
+    fn allocate(&self, icon_ids: &[&str], range: &RangeInclusive<char>)
+        -> Result<Vec<char>, Error>;
+}
+
+/// Assigns codepoints in the order in which icons are found in the source directory.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SequentialAllocator;
+
+impl CodepointAllocator for SequentialAllocator {
+    fn allocate(
+        &self,
+        icon_ids: &[&str],
+        range: &RangeInclusive<char>,
+    ) -> Result<Vec<char>, Error> {
+        Assignments::new(icon_ids).assign_remaining(0..icon_ids.len(), range)
+    }
+}
+
+/// Assigns codepoints in the order of the icon IDs.
+///
+/// Unlike [`SequentialAllocator`], the result does not depend on the order of directory entries,
+/// which differs between file systems.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SortedAllocator;
+
+impl CodepointAllocator for SortedAllocator {
+    fn allocate(
+        &self,
+        icon_ids: &[&str],
+        range: &RangeInclusive<char>,
+    ) -> Result<Vec<char>, Error> {
+        Assignments::new(icon_ids).assign_remaining(sorted_indices(icon_ids), range)
+    }
+}
+
+/// Derives codepoints from a stable hash of the icon IDs.
+///
+/// The codepoint of an icon stays the same when other icons are added or removed, unless their
+/// hashes collide. Colliding icons get the next free codepoint in the order of their IDs.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HashAllocator;
+
+impl CodepointAllocator for HashAllocator {
+    fn allocate(
+        &self,
+        icon_ids: &[&str],
+        range: &RangeInclusive<char>,
+    ) -> Result<Vec<char>, Error> {
+        let capacity = num_private_use_codepoints(range);
+        if icon_ids.len() > capacity {
+            return Err(Error::CodepointsExhausted(icon_ids.len(), capacity));
+        }
+        let codepoints: Vec<char> = private_use_codepoints(range).collect();
+
+        let mut assignments = Assignments::new(icon_ids);
+        for index in sorted_indices(icon_ids) {
+            let start = (fnv1a(icon_ids[index]) % capacity as u64) as usize;
+            let codepoint = (0..capacity)
+                .map(|offset| codepoints[(start + offset) % capacity])
+                .find(|&codepoint| assignments.is_free(codepoint))
+                .expect("capacity should suffice for all icons");
+            assignments.assign(index, codepoint)?;
+        }
+
+        assignments.assign_remaining([], range)
+    }
+}
+
+/// Assigns a separate block of codepoints to each namespace.
+///
+/// Each entry consists of a pattern that is matched against the icon ID and the first codepoint
+/// of the block, e.g. `("jam/*", '\u{E100}')`. Patterns follow the same rules as
+/// [`Options::exclude_ids`](super::Options::exclude_ids) and the first matching entry applies.
+/// A block ends before the start of the next block or at the end of the codepoint range.
+/// Icons that do not belong to any namespace get the remaining codepoints of the range.
+#[derive(Clone, Debug, Default)]
+pub struct NamespaceAllocator {
+    pub namespaces: Vec<(String, char)>,
+}
+
+impl CodepointAllocator for NamespaceAllocator {
+    fn allocate(
+        &self,
+        icon_ids: &[&str],
+        range: &RangeInclusive<char>,
+    ) -> Result<Vec<char>, Error> {
+        let mut block_starts: Vec<char> = self.namespaces.iter().map(|&(_, start)| start).collect();
+        block_starts.sort_unstable();
+
+        let mut assignments = Assignments::new(icon_ids);
+        for (pattern, start) in &self.namespaces {
+            let block_end = block_starts
+                .iter()
+                .find(|&block_start| block_start > start)
+                .and_then(|&block_start| char::from_u32(block_start as u32 - 1))
+                .unwrap_or(*range.end());
+            let block = *start..=block_end;
+
+            let members = (0..icon_ids.len()).filter(|&index| {
+                assignments.codepoints[index].is_none() && matches_pattern(pattern, icon_ids[index])
+            });
+            let members: Vec<usize> = members.collect();
+            let mut codepoints = private_use_codepoints(&block);
+            for &index in &members {
+                let codepoint = codepoints.next().ok_or_else(|| {
+                    Error::CodepointsExhausted(members.len(), num_private_use_codepoints(&block))
+                })?;
+                assignments.assign(index, codepoint)?;
+            }
+        }
+
+        assignments.assign_remaining(0..icon_ids.len(), range)
+    }
+}
+
+/// Assigns given codepoints to icons, e.g. from a manifest.
+///
+/// Explicit codepoints may lie outside of the codepoint range. Icons without an explicit
+/// codepoint get the remaining codepoints of the range in order.
+#[derive(Clone, Debug, Default)]
+pub struct ExplicitAllocator {
+    pub codepoints: HashMap<String, char>,
+}
+
+impl CodepointAllocator for ExplicitAllocator {
+    fn allocate(
+        &self,
+        icon_ids: &[&str],
+        range: &RangeInclusive<char>,
+    ) -> Result<Vec<char>, Error> {
+        let mut assignments = Assignments::new(icon_ids);
+        for (index, icon_id) in icon_ids.iter().enumerate() {
+            if let Some(&codepoint) = self.codepoints.get(*icon_id) {
+                assignments.assign(index, codepoint)?;
+            }
+        }
+
+        assignments.assign_remaining(0..icon_ids.len(), range)
+    }
+}
+
+/// Keeps the codepoints of icons from a previously published font.
+///
+/// Also keeps the fixed codepoints of a [`Manifest`](super::Manifest).
+///
+/// Icons of the baseline keep their codepoints and codepoints of removed icons are not reused.
+/// New icons get the codepoints proposed by the inner allocator if they are free and the next
+/// free codepoints of the range otherwise.
+#[derive(Clone, Debug)]
+pub struct BaselineAllocator {
+    pub baseline: IconMap,
+    pub allocator: Arc<dyn CodepointAllocator>,
+}
+
+impl CodepointAllocator for BaselineAllocator {
+    fn allocate(
+        &self,
+        icon_ids: &[&str],
+        range: &RangeInclusive<char>,
+    ) -> Result<Vec<char>, Error> {
+        let mut assignments = Assignments::new(icon_ids);
+        for (index, icon_id) in icon_ids.iter().enumerate() {
+            if let Some(&codepoint) = self.baseline.get(*icon_id) {
+                assignments.assign(index, codepoint)?;
+            }
+        }
+        assignments.reserved.extend(self.baseline.values().copied());
+
+        let new_indices: Vec<usize> = (0..icon_ids.len())
+            .filter(|&index| assignments.codepoints[index].is_none())
+            .collect();
+        let new_ids: Vec<&str> = new_indices.iter().map(|&index| icon_ids[index]).collect();
+        let proposed = self.allocator.allocate(&new_ids, range)?;
+        for (index, codepoint) in new_indices.into_iter().zip(proposed) {
+            if assignments.is_free(codepoint) {
+                assignments.assign(index, codepoint)?;
+            }
+        }
+
+        assignments.assign_remaining(0..icon_ids.len(), range)
+    }
+}
+
+/// Codepoints assigned to icons so far
+struct Assignments<'a> {
+    icon_ids: &'a [&'a str],
+    codepoints: Vec<Option<char>>,
+    owners: HashMap<char, usize>,
+    /// Codepoints that are not assigned to any icon
+    reserved: HashSet<char>,
+}
+
+impl<'a> Assignments<'a> {
+    fn new(icon_ids: &'a [&'a str]) -> Self {
+        Self {
+            icon_ids,
+            codepoints: vec![None; icon_ids.len()],
+            owners: HashMap::new(),
+            reserved: HashSet::new(),
+        }
+    }
+
+    fn is_free(&self, codepoint: char) -> bool {
+        !self.owners.contains_key(&codepoint) && !self.reserved.contains(&codepoint)
+    }
+
+    fn assign(&mut self, index: usize, codepoint: char) -> Result<(), Error> {
+        if let Some(&owner) = self.owners.get(&codepoint) {
+            return Err(Error::CodepointConflict(
+                codepoint as u32,
+                self.icon_ids[owner].to_string(),
+                self.icon_ids[index].to_string(),
+            ));
+        }
+        self.owners.insert(codepoint, index);
+        self.codepoints[index] = Some(codepoint);
+
+        Ok(())
+    }
+
+    /// Assigns the free codepoints of the range in ascending order to the icons without a
+    /// codepoint, which are visited in the given order.
+    fn assign_remaining(
+        mut self,
+        order: impl IntoIterator<Item = usize>,
+        range: &RangeInclusive<char>,
+    ) -> Result<Vec<char>, Error> {
+        let mut free_codepoints = private_use_codepoints(range);
+        for index in order {
+            if self.codepoints[index].is_some() {
+                continue;
+            }
+            let codepoint = free_codepoints
+                .by_ref()
+                .find(|&codepoint| self.is_free(codepoint))
+                .ok_or_else(|| {
+                    Error::CodepointsExhausted(
+                        self.icon_ids.len(),
+                        num_private_use_codepoints(range),
+                    )
+                })?;
+            self.assign(index, codepoint)?;
+        }
+
+        Ok(self
+            .codepoints
+            .into_iter()
+            .map(|codepoint| codepoint.expect("all icons should have a codepoint"))
+            .collect())
+    }
+}
+
+/// Returns the indices of the icon IDs in the order of the IDs.
+fn sorted_indices(icon_ids: &[&str]) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..icon_ids.len()).collect();
+    indices.sort_by_key(|&index| icon_ids[index]);
+    indices
+}
+
+/// 64-bit FNV-1a hash, which is stable across platforms and Rust versions
+fn fnv1a(value: &str) -> u64 {
+    value.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Returns the codepoints of private use areas within the given range in ascending order.
+pub fn private_use_codepoints(range: &RangeInclusive<char>) -> impl Iterator<Item = char> {
+    private_use_ranges(range).flat_map(|range| range.filter_map(char::from_u32))
+}
+
+/// Returns the number of codepoints of private use areas within the given range.
+pub fn num_private_use_codepoints(range: &RangeInclusive<char>) -> usize {
+    private_use_ranges(range)
+        .map(|range| (range.end() + 1 - range.start()) as usize)
+        .sum()
+}
+
+fn private_use_ranges(range: &RangeInclusive<char>) -> impl Iterator<Item = RangeInclusive<u32>> {
+    let (start, end) = (*range.start() as u32, *range.end() as u32);
+
+    PRIVATE_USE_AREAS
+        .into_iter()
+        .map(move |area| (*area.start()).max(start)..=(*area.end()).min(end))
+        .filter(|range| !range.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ICON_IDS: [&str; 4] = ["jam/b", "c", "jam/a", "a"];
+    const RANGE: RangeInclusive<char> = '\u{E000}'..='\u{F8FF}';
+
+    #[test]
+    fn codepoints_across_planes() {
+        let range = '\u{F8FE}'..='\u{F0001}';
+
+        let codepoints: Vec<char> = private_use_codepoints(&range).collect();
+
+        assert_eq!(
+            codepoints,
+            ['\u{F8FE}', '\u{F8FF}', '\u{F0000}', '\u{F0001}']
+        );
+        assert_eq!(num_private_use_codepoints(&range), 4);
+        assert_eq!(
+            num_private_use_codepoints(&('\u{F0000}'..='\u{10FFFD}')),
+            2 * 65534
+        );
+    }
+
+    #[test]
+    fn sequential_and_sorted() {
+        let sequential = SequentialAllocator.allocate(&ICON_IDS, &RANGE).unwrap();
+        let sorted = SortedAllocator.allocate(&ICON_IDS, &RANGE).unwrap();
+
+        assert_eq!(sequential, ['\u{E000}', '\u{E001}', '\u{E002}', '\u{E003}']);
+        assert_eq!(sorted, ['\u{E003}', '\u{E001}', '\u{E002}', '\u{E000}']);
+    }
+
+    #[test]
+    fn stable_hash() {
+        let all = HashAllocator.allocate(&ICON_IDS, &RANGE).unwrap();
+        let subset = HashAllocator.allocate(&ICON_IDS[1..3], &RANGE).unwrap();
+
+        assert_eq!(&all[1..3], subset);
+        assert!(all.iter().all(|&codepoint| RANGE.contains(&codepoint)));
+    }
+
+    #[test]
+    fn namespace_blocks() {
+        let allocator = NamespaceAllocator {
+            namespaces: vec![("jam/*".to_string(), '\u{E100}')],
+        };
+
+        let codepoints = allocator.allocate(&ICON_IDS, &RANGE).unwrap();
+
+        assert_eq!(codepoints, ['\u{E100}', '\u{E000}', '\u{E101}', '\u{E001}']);
+    }
+
+    #[test]
+    fn explicit_codepoints() {
+        let allocator = ExplicitAllocator {
+            codepoints: HashMap::from([("c".to_string(), '\u{E000}'), ("a".to_string(), 'A')]),
+        };
+        let conflicting = ExplicitAllocator {
+            codepoints: HashMap::from([
+                ("c".to_string(), '\u{E000}'),
+                ("a".to_string(), '\u{E000}'),
+            ]),
+        };
+
+        let codepoints = allocator.allocate(&ICON_IDS, &RANGE).unwrap();
+
+        assert_eq!(codepoints, ['\u{E001}', '\u{E000}', '\u{E002}', 'A']);
+        assert!(matches!(
+            conflicting.allocate(&ICON_IDS, &RANGE),
+            Err(Error::CodepointConflict(0xE000, _, _))
+        ));
+    }
+
+    #[test]
+    fn baseline_codepoints() {
+        let allocator = BaselineAllocator {
+            baseline: IconMap::from_iter([
+                ("a".to_string(), '\u{E005}'),
+                ("removed".to_string(), '\u{E000}'),
+            ]),
+            allocator: Arc::new(SequentialAllocator),
+        };
+
+        let codepoints = allocator.allocate(&["b", "a", "c"], &RANGE).unwrap();
+
+        assert_eq!(codepoints, ['\u{E002}', '\u{E005}', '\u{E001}']);
+    }
+}
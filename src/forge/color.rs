@@ -0,0 +1,185 @@
+use kurbo::{Affine, BezPath, Point};
+
+/// Format of the color glyphs that are added for multi-colored icons.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorFormat {
+    /// Layers of solid colors in `COLR` version 0 with a `CPAL` palette.
+    ///
+    /// Gradients are reduced to the average color of their stops.
+    ColrV0,
+    /// Layers of solid colors and linear or radial gradients in `COLR` version 1 with a
+    /// `CPAL` palette.
+    ///
+    /// Opacity is kept for all paints including `currentColor`, which may also be the color of
+    /// gradient stops.
+    ColrV1,
+}
+
+/// Paint of a layer of a color glyph
+#[derive(Clone, Debug, PartialEq)]
+pub enum Paint {
+    /// Color of the surrounding text, `currentColor` in SVG, with the given alpha
+    Foreground {
+        alpha: u8,
+    },
+    Solid(Color),
+    Gradient(Gradient),
+}
+
+/// Gradient with coordinates given by a transform into the coordinates of the outlines
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    pub kind: GradientKind,
+    pub stops: Vec<ColorStop>,
+    pub extend: Extend,
+    pub transform: Affine,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GradientKind {
+    /// Colors change along the line from the start to the end point
+    Linear { start: Point, end: Point },
+    /// Colors change from the start circle to the end circle, in SVG from the focal point to
+    /// the outer circle
+    Radial {
+        start: Point,
+        start_radius: f64,
+        end: Point,
+        end_radius: f64,
+    },
+}
+
+/// Color at an offset between 0 and 1 along a gradient
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorStop {
+    pub offset: f32,
+    pub color: Color,
+    /// Whether the stop has the color of the surrounding text, `currentColor` in SVG, with the
+    /// alpha of the color
+    pub foreground: bool,
+}
+
+/// How a gradient continues beyond its first and last stop
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Extend {
+    /// Colors of the first and last stop continue
+    #[default]
+    Pad,
+    Repeat,
+    Reflect,
+}
+
+/// Color with straight alpha
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    pub alpha: u8,
+}
+
+/// Color that `currentColor` is preferably replaced with before colors are resolved, so that
+/// it can be told apart from the color it resolves to
+pub(crate) const CURRENT_COLOR: Color = Color {
+    red: 0x01,
+    green: 0xCC,
+    blue: 0xFE,
+    alpha: 0xFF,
+};
+
+/// Default fill color of SVG
+pub(crate) const BLACK: Color = Color {
+    red: 0,
+    green: 0,
+    blue: 0,
+    alpha: 0xFF,
+};
+
+impl Paint {
+    /// Paint of the surrounding text without transparency
+    pub const FOREGROUND: Paint = Paint::Foreground { alpha: 0xFF };
+
+    /// Returns the paint for outlines that are transformed by the affine transform.
+    pub(crate) fn transformed(&self, affine: Affine) -> Paint {
+        match self {
+            Paint::Gradient(gradient) => Paint::Gradient(Gradient {
+                transform: affine * gradient.transform,
+                ..gradient.clone()
+            }),
+            Paint::Foreground { .. } | Paint::Solid(_) => self.clone(),
+        }
+    }
+
+    /// Returns the paint with gradients reduced to their average color, gradients whose stops
+    /// all have the foreground color are reduced to the foreground color.
+    pub(crate) fn flattened(&self) -> Paint {
+        match self {
+            Paint::Gradient(gradient)
+                if !gradient.stops.is_empty()
+                    && gradient.stops.iter().all(|stop| stop.foreground) =>
+            {
+                Paint::Foreground {
+                    alpha: gradient.average_color().alpha,
+                }
+            }
+            Paint::Gradient(gradient) => Paint::Solid(gradient.average_color()),
+            Paint::Foreground { .. } | Paint::Solid(_) => self.clone(),
+        }
+    }
+}
+
+impl Gradient {
+    /// Returns the average color of the stops, stops with the foreground color only contribute
+    /// their alpha.
+    pub fn average_color(&self) -> Color {
+        let average = |foreground: bool, value: fn(&Color) -> u8| {
+            let values: Vec<f32> = self
+                .stops
+                .iter()
+                .filter(|stop| foreground || !stop.foreground)
+                .map(|stop| value(&stop.color) as f32)
+                .collect();
+            let len = values.len().max(1) as f32;
+            (values.iter().sum::<f32>() / len).round() as u8
+        };
+
+        Color {
+            red: average(false, |color| color.red),
+            green: average(false, |color| color.green),
+            blue: average(false, |color| color.blue),
+            alpha: average(true, |color| color.alpha),
+        }
+    }
+}
+
+impl Color {
+    /// Returns the color with its alpha multiplied by the opacity.
+    pub(crate) fn with_opacity(self, opacity: f32) -> Self {
+        Self {
+            alpha: (self.alpha as f32 * opacity.clamp(0.0, 1.0)).round() as u8,
+            ..self
+        }
+    }
+}
+
+/// Splits the outlines of an icon into layers of consecutive paths with the same paint.
+///
+/// Returns no layers if all paths use the opaque foreground color, since the glyph itself is
+/// drawn in the foreground color.
+pub(crate) fn color_layers(bez_paths: &[BezPath], paints: &[Paint]) -> Vec<(Paint, Vec<BezPath>)> {
+    if paints.iter().all(|paint| *paint == Paint::FOREGROUND) {
+        return vec![];
+    }
+
+    let mut layers: Vec<(Paint, Vec<BezPath>)> = vec![];
+    for (bez_path, paint) in bez_paths.iter().zip(paints) {
+        match layers.last_mut() {
+            Some((layer_paint, layer_paths)) if layer_paint == paint => {
+                layer_paths.push(bez_path.clone());
+            }
+            _ => layers.push((paint.clone(), vec![bez_path.clone()])),
+        }
+    }
+
+    layers
+}
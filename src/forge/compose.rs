@@ -0,0 +1,192 @@
+use kurbo::{Affine, BezPath, Vec2};
+use std::iter;
+
+use super::{
+    outline::knock_out,
+    svg::{bounding_box, SvgIcon},
+};
+
+/// Icon that is composed of a base icon and a badge in one of its corners.
+///
+/// The badge is scaled to fit into a square in the corner of the base icon's view box. Its
+/// silhouette, widened by the gap, is knocked out of the base icon so that a clear gap
+/// separates both, e.g. `"folder-with-lock"` composed of `"folder"` and `"lock"`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComposedIcon {
+    pub id: String,
+    /// ID of the icon the badge is placed on
+    pub base_id: String,
+    /// ID of the icon that is placed as a badge
+    pub badge_id: String,
+    pub corner: Corner,
+    /// Size of the badge as a fraction of the smaller side of the base icon's view box
+    pub scale: f64,
+    /// Width of the gap around the badge as a fraction of the smaller side of the base icon's
+    /// view box
+    pub gap: f64,
+}
+
+/// Corner of an icon as seen on screen
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+impl Corner {
+    /// Returns the corner on the other side, as seen in mirrored icons.
+    pub(crate) fn mirrored(self) -> Self {
+        match self {
+            Corner::TopLeft => Corner::TopRight,
+            Corner::TopRight => Corner::TopLeft,
+            Corner::BottomLeft => Corner::BottomRight,
+            Corner::BottomRight => Corner::BottomLeft,
+        }
+    }
+}
+
+impl ComposedIcon {
+    /// Places the badge on the base icon, curves deviate at most by the tolerance in SVG user
+    /// units where outlines are cut.
+    pub(crate) fn apply(&self, base: &SvgIcon, badge: &SvgIcon, tolerance: f64) -> SvgIcon {
+        let view_box = base.view_box;
+        let size = view_box.width().min(view_box.height());
+
+        let bounds = bounding_box(&badge.bez_paths);
+        let badge_size = bounds.width().max(bounds.height());
+        if badge_size <= 0.0 {
+            return base.clone();
+        }
+        let factor = self.scale * size / badge_size;
+        let (width, height) = (bounds.width() * factor, bounds.height() * factor);
+
+        let x = match self.corner {
+            Corner::TopLeft | Corner::BottomLeft => view_box.x0,
+            Corner::TopRight | Corner::BottomRight => view_box.x1 - width,
+        };
+        let y = match self.corner {
+            Corner::TopLeft | Corner::TopRight => view_box.y0,
+            Corner::BottomLeft | Corner::BottomRight => view_box.y1 - height,
+        };
+        let affine = Affine::translate(Vec2::new(x, y))
+            * Affine::scale(factor)
+            * Affine::translate(-bounds.origin().to_vec2());
+        let badge_paths: Vec<BezPath> = badge
+            .bez_paths
+            .iter()
+            .map(|bez_path| affine * bez_path)
+            .collect();
+
+        // Paths with different paints are cut separately to keep their paints
+        let mut bez_paths = vec![];
+        let mut paints = vec![];
+        let mut start = 0;
+        while start < base.bez_paths.len() {
+            let paint = &base.paints[start];
+            let len = base.paints[start..]
+                .iter()
+                .take_while(|&other| other == paint)
+                .count();
+            let cut_paths = knock_out(
+                base.bez_paths[start..start + len].to_vec(),
+                &badge_paths,
+                self.gap * size,
+                tolerance,
+            );
+            paints.extend(iter::repeat_n(paint.clone(), cut_paths.len()));
+            bez_paths.extend(cut_paths);
+            start += len;
+        }
+        bez_paths.extend(badge_paths);
+        paints.extend(badge.paints.iter().map(|paint| paint.transformed(affine)));
+
+        SvgIcon {
+            bez_paths,
+            paints,
+            view_box,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        forge::{process_svg_files, Color, Options, Paint, Report},
+        Error,
+    };
+    use kurbo::{Circle, Rect, Shape};
+
+    #[test]
+    fn badge_in_corner() {
+        let base = SvgIcon {
+            bez_paths: vec![Rect::new(2.0, 2.0, 22.0, 22.0).to_path(0.1)],
+            paints: vec![Paint::FOREGROUND],
+            view_box: Rect::new(0.0, 0.0, 24.0, 24.0),
+        };
+        let badge = SvgIcon {
+            bez_paths: vec![Circle::new((50.0, 50.0), 40.0).to_path(0.1)],
+            paints: vec![Paint::Solid(Color {
+                red: 0xFF,
+                green: 0,
+                blue: 0,
+                alpha: 0xFF,
+            })],
+            view_box: Rect::new(0.0, 0.0, 100.0, 100.0),
+        };
+        let composed_icon = ComposedIcon {
+            id: "square-with-dot".to_string(),
+            base_id: "square".to_string(),
+            badge_id: "dot".to_string(),
+            corner: Corner::TopRight,
+            scale: 0.5,
+            gap: 0.125,
+        };
+
+        let icon = composed_icon.apply(&base, &badge, 0.01);
+
+        assert_eq!(icon.view_box, base.view_box);
+        assert_eq!(icon.paints.len(), icon.bez_paths.len());
+        assert_eq!(icon.paints.last(), badge.paints.last());
+        let badge_bounds = icon.bez_paths.last().unwrap().bounding_box();
+        assert!((badge_bounds.x0 - 12.0).abs() < 1e-9);
+        assert!((badge_bounds.x1 - 24.0).abs() < 1e-9);
+        assert!((badge_bounds.y0 - 0.0).abs() < 1e-9);
+        assert!((badge_bounds.y1 - 12.0).abs() < 1e-9);
+
+        // The gap of 3 units around the badge is cleared
+        let base_area: f64 = icon.bez_paths[..icon.bez_paths.len() - 1]
+            .iter()
+            .map(|bez_path| bez_path.area())
+            .sum();
+        let cleared_area = 20.0 * 20.0 - base_area.abs();
+        assert!(cleared_area > 0.25 * std::f64::consts::PI * 9.0 * 9.0);
+        assert!(!icon.bez_paths[0].contains((12.0, 12.0).into()));
+        assert!(icon.bez_paths[0].contains((8.0, 16.0).into()));
+    }
+
+    #[test]
+    fn missing_part() {
+        let options = Options {
+            composed_icons: vec![ComposedIcon {
+                id: "square-with-dot".to_string(),
+                base_id: "square".to_string(),
+                badge_id: "dot".to_string(),
+                corner: Corner::TopRight,
+                scale: 0.5,
+                gap: 0.125,
+            }],
+            ..Options::default()
+        };
+
+        let result = process_svg_files(vec![], &options, &mut Report::default());
+
+        assert!(matches!(
+            result,
+            Err(Error::MissingIcon(id, part)) if id == "square-with-dot" && part == "square"
+        ));
+    }
+}
@@ -1,10 +1,12 @@
-use kurbo::BezPath;
 use std::{
     fs,
     path::{Path, PathBuf},
 };
 
-use super::svg::simplify_svg;
+use super::{
+    svg::{simplify_svg, SvgIcon},
+    Options,
+};
 use crate::Error;
 
 pub fn get_font_name(destination: &Path) -> Result<String, Error> {
@@ -70,10 +72,10 @@ fn get_svg_file_paths(dir: &Path) -> Result<Vec<PathBuf>, Error> {
     Ok(svg_files)
 }
 
-/// Reads an SVG file, simplifies it with [`usvg`], replaces Cubic Bézier paths with Quadratic Bézier
-/// paths, and returns a vector of [`BezPath`]
-pub fn read_svg_file(file_path: &Path) -> Option<Vec<BezPath>> {
+/// Reads an SVG file, simplifies it with [`usvg`], converts strokes to outlines,
+/// and returns the resulting [`BezPath`]s together with the view box
+pub fn read_svg_file(file_path: &Path, options: &Options) -> Option<SvgIcon> {
     let svg_data = fs::read_to_string(file_path).ok()?;
-    let glyph = simplify_svg(svg_data).ok()?;
-    Some(glyph)
+    let icon = simplify_svg(svg_data, options).ok()?;
+    Some(icon)
 }
@@ -1,23 +1,39 @@
-use write_fonts::{tables::glyf::SimpleGlyph, FontBuilder};
+use write_fonts::FontBuilder;
 
-pub use self::glyph::create_glyph;
+pub(crate) use self::glyph::{create_color_layer, find_duplicates, scale_factor};
+pub use self::glyph::{create_glyph, ColorLayer, IconGlyph};
 
+mod composite;
 mod glyph;
 mod table;
+#[cfg(test)]
+mod test_utils;
 
 use self::table::add_font_tables;
-use crate::Error;
+use crate::{forge::Options, Error};
 
-// Should be positive and < i16::MAX
-const ADVANCE: u16 = 1000;
+/// Rounds a value to font units.
+///
+/// Fails instead of wrapping around if the value does not fit into the target type.
+pub(crate) fn to_font_units<T: TryFrom<i64>>(value: f64) -> Result<T, Error> {
+    let rounded = value.round();
+    if rounded.abs() < i64::MAX as f64 {
+        if let Ok(font_units) = T::try_from(rounded as i64) {
+            return Ok(font_units);
+        }
+    }
+
+    Err(Error::FontUnitsOverflow(value))
+}
 
 /// Creates a font builder that provides fine-grained control over TrueType font generation.
 pub fn build_font(
-    named_glyphs: Vec<(String, SimpleGlyph)>,
+    named_glyphs: Vec<(String, IconGlyph)>,
     name: &str,
-) -> Result<FontBuilder, Error> {
+    options: &Options,
+) -> Result<FontBuilder<'static>, Error> {
     let mut font = FontBuilder::new();
-    add_font_tables(&mut font, name, &named_glyphs)?;
+    add_font_tables(&mut font, name, &named_glyphs, options)?;
 
     Ok(font)
 }
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use write_fonts::tables::glyf::{Bbox, Contour, SimpleGlyph};
+
+use super::glyph::num_points;
+
+/// Minimum number of points a glyph must have to be reused as a component
+const MIN_COMPONENT_POINTS: usize = 8;
+
+/// Points of a contour relative to its first point
+type ContourShape = Vec<(i32, i32, bool)>;
+
+/// Occurrence of the contours of a base glyph in another glyph
+struct Occurrence {
+    base: usize,
+    offset: (i32, i32),
+    covered: Vec<bool>,
+    num_points: usize,
+}
+
+/// Glyph that reuses the outlines of another glyph as a component.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Composition {
+    /// Index of the glyph whose outlines are reused
+    pub base: usize,
+    /// Offset at which the outlines of the base glyph are placed
+    pub offset: (i16, i16),
+    /// Outlines that are not covered by the base glyph
+    pub remainder: Option<SimpleGlyph>,
+}
+
+/// Finds glyphs that contain all contours of another glyph, possibly shifted.
+///
+/// Only pairs of glyph indices for which `is_candidate(glyph, base)` returns `true` are
+/// considered. Base glyphs are never composed themselves, so components are nested only once.
+pub fn find_compositions(
+    glyphs: &[&SimpleGlyph],
+    is_candidate: impl Fn(usize, usize) -> bool,
+) -> Vec<Option<Composition>> {
+    let contours: Vec<Vec<(ContourShape, (i32, i32))>> = glyphs
+        .iter()
+        .map(|glyph| glyph.contours.iter().map(contour_shape).collect())
+        .collect();
+
+    // Glyphs by the shape of their first contour
+    let mut first_shapes: HashMap<&ContourShape, Vec<usize>> = HashMap::new();
+    for (index, glyph_contours) in contours.iter().enumerate() {
+        if let Some((shape, _)) = glyph_contours.first() {
+            first_shapes.entry(shape).or_default().push(index);
+        }
+    }
+
+    let mut compositions: Vec<Option<Composition>> = vec![None; glyphs.len()];
+    let mut is_base = vec![false; glyphs.len()];
+
+    for (index, glyph_contours) in contours.iter().enumerate() {
+        if is_base[index] {
+            continue;
+        }
+
+        let mut best: Option<Occurrence> = None;
+        for (shape, (x, y)) in glyph_contours {
+            for &base in first_shapes.get(shape).into_iter().flatten() {
+                let num_points = num_points(glyphs[base]);
+                if base == index
+                    || compositions[base].is_some()
+                    || num_points < MIN_COMPONENT_POINTS
+                    || best
+                        .as_ref()
+                        .is_some_and(|best| best.num_points >= num_points)
+                    || !is_candidate(index, base)
+                {
+                    continue;
+                }
+
+                let (base_x, base_y) = contours[base][0].1;
+                let offset = (x - base_x, y - base_y);
+                if let Some(covered) = covered_contours(glyph_contours, &contours[base], offset) {
+                    best = Some(Occurrence {
+                        base,
+                        offset,
+                        covered,
+                        num_points,
+                    });
+                }
+            }
+        }
+
+        let Some(Occurrence {
+            base,
+            offset: (dx, dy),
+            covered,
+            ..
+        }) = best
+        else {
+            continue;
+        };
+        let (Ok(dx), Ok(dy)) = (i16::try_from(dx), i16::try_from(dy)) else {
+            continue;
+        };
+
+        let remaining_contours: Vec<Contour> = glyphs[index]
+            .contours
+            .iter()
+            .zip(covered)
+            .filter(|(_, covered)| !covered)
+            .map(|(contour, _)| contour.clone())
+            .collect();
+        let remainder = (!remaining_contours.is_empty()).then(|| {
+            let mut glyph = SimpleGlyph {
+                bbox: Bbox::default(),
+                contours: remaining_contours,
+                instructions: vec![],
+            };
+            glyph.recompute_bounding_box();
+            glyph
+        });
+
+        is_base[base] = true;
+        compositions[index] = Some(Composition {
+            base,
+            offset: (dx, dy),
+            remainder,
+        });
+    }
+
+    compositions
+}
+
+/// Returns which contours of a glyph are covered by the contours of a base glyph shifted by
+/// the offset, or `None` if not all contours of the base glyph are found.
+fn covered_contours(
+    contours: &[(ContourShape, (i32, i32))],
+    base_contours: &[(ContourShape, (i32, i32))],
+    (dx, dy): (i32, i32),
+) -> Option<Vec<bool>> {
+    let mut covered = vec![false; contours.len()];
+    for (base_shape, (x, y)) in base_contours {
+        let position = (x + dx, y + dy);
+        let index = contours
+            .iter()
+            .zip(&covered)
+            .position(|((shape, start), &covered)| {
+                !covered && *start == position && shape == base_shape
+            })?;
+        covered[index] = true;
+    }
+
+    Some(covered)
+}
+
+fn contour_shape(contour: &Contour) -> (ContourShape, (i32, i32)) {
+    let (x, y) = contour
+        .iter()
+        .next()
+        .map_or((0, 0), |point| (point.x as i32, point.y as i32));
+    let shape = contour
+        .iter()
+        .map(|point| (point.x as i32 - x, point.y as i32 - y, point.on_curve))
+        .collect();
+
+    (shape, (x, y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forge::font::test_utils::{simple_glyph as glyph, square};
+
+    #[test]
+    fn shifted_base_with_modifier() {
+        let base = glyph(vec![square(0, 0, 800), square(100, 100, 600)]);
+        let with_dot = glyph(vec![
+            square(100, 0, 800),
+            square(200, 100, 600),
+            square(850, 850, 100),
+        ]);
+        let unrelated = glyph(vec![square(0, 0, 500), square(100, 100, 600)]);
+
+        let compositions = find_compositions(&[&base, &with_dot, &unrelated], |_, _| true);
+
+        assert_eq!(compositions[0], None);
+        assert_eq!(
+            compositions[1],
+            Some(Composition {
+                base: 0,
+                offset: (100, 0),
+                remainder: Some(glyph(vec![square(850, 850, 100)])),
+            })
+        );
+        assert_eq!(compositions[2], None);
+    }
+}
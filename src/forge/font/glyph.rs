@@ -1,9 +1,17 @@
-use kurbo::{BezPath, PathEl, Point};
+use kurbo::{Affine, BezPath, CubicBez, PathEl, Point, Rect};
 use read_fonts::tables::glyf::CurvePoint;
-use write_fonts::tables::glyf::{Bbox, SimpleGlyph};
+use std::collections::HashMap;
+use write_fonts::tables::glyf::{Bbox, Contour, SimpleGlyph};
 
-use super::ADVANCE;
-use crate::{forge::svg::bounding_box, Error};
+use super::to_font_units;
+use crate::{
+    forge::{
+        adjustment::is_positive,
+        svg::{bounding_box, cubic_to_quadratic_points},
+        AdvanceWidth, Fit, Options, Paint,
+    },
+    Error,
+};
 
 #[allow(dead_code)]
 pub trait BboxMetrics {
@@ -26,82 +34,319 @@ impl BboxMetrics for Bbox {
     }
 }
 
+/// Maximum ratio of width to height of proportional glyphs, wider icons are scaled down
+const MAX_ASPECT_RATIO: f64 = 16.0;
+
+/// A glyph together with its advance width
+#[derive(Clone, Debug)]
+pub struct IconGlyph {
+    pub glyph: SimpleGlyph,
+    pub advance_width: u16,
+    /// Layers of the color glyph from bottom to top, empty for monochrome icons
+    pub layers: Vec<ColorLayer>,
+}
+
+/// Outlines of an icon that are painted alike in a color glyph
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorLayer {
+    pub glyph: SimpleGlyph,
+    pub paint: Paint,
+}
+
+impl IconGlyph {
+    /// Returns the glyph mirrored horizontally within its advance width.
+    pub fn mirrored(&self) -> Result<Self, Error> {
+        let mirror = Affine::new([-1.0, 0.0, 0.0, 1.0, self.advance_width as f64, 0.0]);
+        let layers = self
+            .layers
+            .iter()
+            .map(|layer| {
+                Ok(ColorLayer {
+                    glyph: mirror_glyph(&layer.glyph, self.advance_width)?,
+                    paint: layer.paint.transformed(mirror),
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Self {
+            glyph: mirror_glyph(&self.glyph, self.advance_width)?,
+            advance_width: self.advance_width,
+            layers,
+        })
+    }
+}
+
+/// Returns the number of points of all contours of a glyph.
+pub(super) fn num_points(glyph: &SimpleGlyph) -> usize {
+    glyph.contours.iter().map(|contour| contour.len()).sum()
+}
+
+fn mirror_glyph(glyph: &SimpleGlyph, advance_width: u16) -> Result<SimpleGlyph, Error> {
+    let advance_width = advance_width as i32;
+    let contours = glyph
+        .contours
+        .iter()
+        .map(|contour| {
+            let mut points = contour
+                .iter()
+                .map(|point| {
+                    Ok(CurvePoint {
+                        x: to_font_units((advance_width - point.x as i32) as f64)?,
+                        ..*point
+                    })
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            // Reversing the points keeps the direction of the contour
+            points.reverse();
+            Ok(Contour::from(points))
+        })
+        .collect::<Result<_, Error>>()?;
+
+    let mut glyph = SimpleGlyph {
+        bbox: Bbox::default(),
+        contours,
+        instructions: glyph.instructions.clone(),
+    };
+    glyph.recompute_bounding_box();
+
+    Ok(glyph)
+}
+
+/// Returns for each glyph the index of the first glyph with the same outlines, advance width
+/// and color layers.
+pub(crate) fn find_duplicates<'a>(
+    icon_glyphs: impl IntoIterator<Item = &'a IconGlyph>,
+) -> Vec<usize> {
+    // Glyphs with the same advance width, number of points and bounds are compared by their
+    // outlines and layers, whose paints are not hashable
+    let mut candidates: HashMap<_, Vec<(usize, &IconGlyph)>> = HashMap::new();
+    icon_glyphs
+        .into_iter()
+        .enumerate()
+        .map(|(index, icon_glyph)| {
+            let glyph = &icon_glyph.glyph;
+            let key = (
+                icon_glyph.advance_width,
+                num_points(glyph),
+                glyph.bbox.bounds(),
+            );
+            let candidates = candidates.entry(key).or_default();
+            match candidates.iter().find(|(_, candidate)| {
+                candidate.glyph.contours == glyph.contours && candidate.layers == icon_glyph.layers
+            }) {
+                Some(&(first_index, _)) => first_index,
+                None => {
+                    candidates.push((index, icon_glyph));
+                    index
+                }
+            }
+        })
+        .collect()
+}
+
+/// Returns the factor by which SVG user units are scaled to font units for an icon with the
+/// given outline bounds and view box.
+pub fn scale_factor(bounds: Rect, view_box: Rect, options: &Options) -> f64 {
+    fit_scale_factor(bounds, view_box, options) * options.adjustment.scale
+}
+
+/// Returns the scale factor before the scale of the adjustment is applied.
+fn fit_scale_factor(bounds: Rect, view_box: Rect, options: &Options) -> f64 {
+    let em = options.units_per_em as f64;
+    let padding = 2.0 * options.adjustment.padding * em;
+    let height = options.vertical_metrics.icon_height * em - padding;
+    let frame = frame(bounds, view_box, options);
+    let scale_factor = match options.advance_width {
+        AdvanceWidth::Square => {
+            let available_width = em - 2.0 * options.side_bearing * em - padding;
+            let scale_x = available_width / frame.width();
+            let scale_y = height / frame.height();
+            match options.adjustment.fit {
+                Fit::Cover if scale_x.is_finite() && scale_y.is_finite() => scale_x.max(scale_y),
+                Fit::Contain | Fit::ViewBox | Fit::Cover => scale_x.min(scale_y),
+            }
+        }
+        AdvanceWidth::Proportional | AdvanceWidth::ViewBox => {
+            height / frame.height().max(frame.width() / MAX_ASPECT_RATIO)
+        }
+    };
+
+    if scale_factor.is_finite() && scale_factor > 0.0 {
+        scale_factor
+    } else {
+        1.0
+    }
+}
+
+/// Returns the area of an icon that is fitted into the icon area and placed inside the
+/// advance width.
+fn frame(bounds: Rect, view_box: Rect, options: &Options) -> Rect {
+    match (options.advance_width, options.adjustment.fit) {
+        (AdvanceWidth::ViewBox, _) | (_, Fit::ViewBox) => view_box,
+        (AdvanceWidth::Square | AdvanceWidth::Proportional, Fit::Contain | Fit::Cover) => bounds,
+    }
+}
+
 struct FontTransform {
     scale_factor: f64,
-    delta_y: i16,
+    delta_x: f64,
+    delta_y: f64,
+    advance_width: u16,
 }
 
 impl FontTransform {
-    fn new(bez_paths: &[BezPath]) -> Self {
-        let bez_bbox = bounding_box(bez_paths);
-        let scale_factor = ADVANCE as f64 / bez_bbox.size().max_side();
+    fn new(bez_paths: &[BezPath], view_box: Rect, options: &Options) -> Result<Self, Error> {
+        let bounds = bounding_box(bez_paths);
+        let frame = frame(bounds, view_box, options);
+        let em = options.units_per_em as f64;
+        let adjustment = &options.adjustment;
+        if !is_positive(adjustment.scale) {
+            return Err(Error::InvalidScale(adjustment.scale));
+        }
 
-        let projected_height = Self::project(bez_bbox.abs().height(), scale_factor);
-        let delta_y = ADVANCE as i16 - (ADVANCE.saturating_sub(projected_height as u16) as i16) / 2;
+        // The scale of the adjustment does not affect the advance width
+        let fit_scale_factor = fit_scale_factor(bounds, view_box, options);
+        let advance_width = match options.advance_width {
+            AdvanceWidth::Square => options.units_per_em,
+            AdvanceWidth::Proportional | AdvanceWidth::ViewBox => to_font_units(
+                (frame.width() * fit_scale_factor).round()
+                    + 2.0 * (options.side_bearing + adjustment.padding) * em,
+            )?,
+        };
+        let scale_factor = fit_scale_factor * adjustment.scale;
 
-        Self {
+        // Center the frame horizontally within the advance width and vertically within the icon
+        // area, which is moved by the baseline offset, and shift it by the offset
+        let height = options.vertical_metrics.icon_height * em;
+        let (offset_x, offset_y) = adjustment.offset;
+        let delta_x = (advance_width as f64 - frame.width() * scale_factor) / 2.0
+            - frame.x0 * scale_factor
+            + offset_x * em;
+        let delta_y = height - (height - frame.height() * scale_factor) / 2.0
+            + frame.y0 * scale_factor
+            + (options.vertical_metrics.baseline_offset + offset_y) * em;
+
+        Ok(Self {
             scale_factor,
+            delta_x,
             delta_y,
-        }
+            advance_width,
+        })
     }
 
-    fn transform_point(&self, point: &Point, on_curve: bool) -> CurvePoint {
-        // SVG origin is in the top left corner, orientation of y is upside down
-        CurvePoint {
-            x: Self::project(point.x, self.scale_factor),
-            y: -Self::project(point.y, self.scale_factor) + self.delta_y,
-            on_curve,
-        }
+    /// Returns the transform from SVG user units to font units.
+    fn affine(&self) -> Affine {
+        Affine::new([
+            self.scale_factor,
+            0.0,
+            0.0,
+            -self.scale_factor,
+            self.delta_x,
+            self.delta_y,
+        ])
     }
 
-    fn project(value: f64, scale_factor: f64) -> i16 {
-        (value * scale_factor).round() as i16
+    fn transform_point(&self, point: &Point, on_curve: bool) -> Result<CurvePoint, Error> {
+        // SVG origin is in the top left corner, orientation of y is upside down
+        Ok(CurvePoint {
+            x: to_font_units(point.x * self.scale_factor + self.delta_x)?,
+            y: to_font_units(-point.y * self.scale_factor + self.delta_y)?,
+            on_curve,
+        })
     }
 }
 
 /// Creates a glyph from Beziér curves created by [`kurbo`].
-/// The path must not contain cubic Bézier curves.
-pub fn create_glyph(bez_paths: Vec<BezPath>) -> Result<SimpleGlyph, Error> {
-    let transform = FontTransform::new(&bez_paths);
+///
+/// The advance width of the glyph is determined by the options, the view box is only relevant
+/// for [`AdvanceWidth::ViewBox`].
+///
+/// Cubic Bézier curves are replaced with quadratic splines once the scale of the glyph is known,
+/// so that the curve tolerance of the options applies in font units. On-curve points that are
+/// implied by two consecutive off-curve points are not stored.
+pub fn create_glyph(
+    bez_paths: Vec<BezPath>,
+    view_box: Rect,
+    options: &Options,
+) -> Result<IconGlyph, Error> {
+    let transform = FontTransform::new(&bez_paths, view_box, options)?;
+
+    Ok(IconGlyph {
+        glyph: transform_outlines(bez_paths, &transform, options)?,
+        advance_width: transform.advance_width,
+        layers: vec![],
+    })
+}
+
+/// Creates a layer of a color glyph that is placed like the glyph with the given outlines.
+pub(crate) fn create_color_layer(
+    bez_paths: Vec<BezPath>,
+    paint: Paint,
+    icon_bez_paths: &[BezPath],
+    view_box: Rect,
+    options: &Options,
+) -> Result<ColorLayer, Error> {
+    let transform = FontTransform::new(icon_bez_paths, view_box, options)?;
+
+    Ok(ColorLayer {
+        glyph: transform_outlines(bez_paths, &transform, options)?,
+        paint: paint.transformed(transform.affine()),
+    })
+}
+
+fn transform_outlines(
+    bez_paths: Vec<BezPath>,
+    transform: &FontTransform,
+    options: &Options,
+) -> Result<SimpleGlyph, Error> {
+    let accuracy = options.curve_tolerance / transform.scale_factor;
     let mut contours = Vec::new();
 
     for bez_path in bez_paths {
         let mut curve_points = Vec::new();
+        let mut start_point = Point::ZERO;
+        let mut current_point = Point::ZERO;
 
         for element in bez_path.elements() {
-            match element {
+            match *element {
                 PathEl::MoveTo(svg_point) => {
-                    let curve_point = transform.transform_point(svg_point, true);
+                    // Each subpath is a separate contour
+                    push_contour(&mut contours, &mut curve_points);
+                    let curve_point = transform.transform_point(&svg_point, true)?;
                     curve_points.push(curve_point);
+                    start_point = svg_point;
+                    current_point = svg_point;
                 }
                 PathEl::LineTo(svg_point) => {
-                    let curve_point = transform.transform_point(svg_point, true);
+                    let curve_point = transform.transform_point(&svg_point, true)?;
                     curve_points.push(curve_point);
+                    current_point = svg_point;
                 }
                 PathEl::QuadTo(svg_control_point, svg_point) => {
-                    let curve_control_point = transform.transform_point(svg_control_point, false);
-                    let curve_point = transform.transform_point(svg_point, true);
+                    let curve_control_point =
+                        transform.transform_point(&svg_control_point, false)?;
+                    let curve_point = transform.transform_point(&svg_point, true)?;
 
                     curve_points.push(curve_control_point);
                     curve_points.push(curve_point);
+                    current_point = svg_point;
                 }
-                PathEl::CurveTo(_, _, _) => {
-                    return Err(Error::GlyphConversion(
-                        "cubic Bézier curves are not allowed".to_string(),
-                    ))
+                PathEl::CurveTo(svg_c1, svg_c2, svg_point) => {
+                    let cubic = CubicBez::new(current_point, svg_c1, svg_c2, svg_point);
+                    for (svg_point, on_curve) in cubic_to_quadratic_points(cubic, accuracy) {
+                        let curve_point = transform.transform_point(&svg_point, on_curve)?;
+                        curve_points.push(curve_point);
+                    }
+                    current_point = svg_point;
                 }
                 PathEl::ClosePath => {
-                    if !curve_points.is_empty() {
-                        contours.push(curve_points.clone().into());
-                        curve_points.clear();
-                    }
+                    push_contour(&mut contours, &mut curve_points);
+                    current_point = start_point;
                 }
             }
         }
 
-        if !curve_points.is_empty() {
-            contours.push(curve_points.into());
-        }
+        push_contour(&mut contours, &mut curve_points);
     }
 
     let mut glyph = SimpleGlyph {
@@ -113,3 +358,286 @@ pub fn create_glyph(bez_paths: Vec<BezPath>) -> Result<SimpleGlyph, Error> {
 
     Ok(glyph)
 }
+
+/// Adds the points as a contour to the glyph after removing implied on-curve points and a
+/// closing point that repeats the start point.
+fn push_contour(contours: &mut Vec<Contour>, curve_points: &mut Vec<CurvePoint>) {
+    if curve_points.is_empty() {
+        return;
+    }
+    // Contours are closed implicitly
+    if curve_points.len() > 1 && curve_points.first() == curve_points.last() {
+        curve_points.pop();
+    }
+    remove_implied_points(curve_points);
+    contours.push(std::mem::take(curve_points).into());
+}
+
+/// Removes on-curve points that lie exactly halfway between two off-curve points.
+///
+/// TrueType rasterizers imply such points, so removing them does not change the outline.
+fn remove_implied_points(curve_points: &mut Vec<CurvePoint>) {
+    let mut i = 0;
+    while i < curve_points.len() && curve_points.len() > 2 {
+        let len = curve_points.len();
+        let prev = curve_points[(i + len - 1) % len];
+        let point = curve_points[i];
+        let next = curve_points[(i + 1) % len];
+
+        let is_implied = point.on_curve
+            && !prev.on_curve
+            && !next.on_curve
+            && 2 * point.x as i32 == prev.x as i32 + next.x as i32
+            && 2 * point.y as i32 == prev.y as i32 + next.y as i32;
+
+        if is_implied {
+            curve_points.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forge::{svg::simplify_svg, Adjustment, VerticalMetrics};
+    use rstest::rstest;
+
+    /// Creates a glyph from a circle made of cubic curves with the given view box size
+    fn circle_glyph(size: f64, options: &Options) -> SimpleGlyph {
+        let r = size / 2.0;
+        let k = r * 0.5523;
+        let svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {size} {size}">
+                <path d="M {r} 0 C {c1} 0 {size} {c2} {size} {r} C {size} {c1} {c1} {size} {r} {size}
+                         C {c2} {size} 0 {c1} 0 {r} C 0 {c2} {c2} 0 {r} 0 Z"/>
+            </svg>"#,
+            c1 = r + k,
+            c2 = r - k,
+        );
+        let icon = simplify_svg(svg, options).expect("failed to simplify SVG");
+        create_glyph(icon.bez_paths, icon.view_box, options)
+            .expect("failed to create glyph")
+            .glyph
+    }
+
+    #[test]
+    fn curve_tolerance_independent_of_view_box() {
+        let options = Options::default();
+        let small = circle_glyph(16.0, &options);
+        let large = circle_glyph(1024.0, &options);
+
+        assert_eq!(num_points(&small), num_points(&large));
+        assert_eq!(small.bbox, large.bbox);
+    }
+
+    #[test]
+    fn curve_tolerance_in_font_units() {
+        let coarse = Options {
+            curve_tolerance: 10.0,
+            ..Options::default()
+        };
+        let fine = Options {
+            curve_tolerance: 0.1,
+            ..Options::default()
+        };
+
+        assert!(num_points(&circle_glyph(24.0, &coarse)) < num_points(&circle_glyph(24.0, &fine)));
+    }
+
+    #[test]
+    fn implied_on_curve_points() {
+        let options = Options::default();
+        let glyph = circle_glyph(24.0, &options);
+
+        // A circle is made of curves only, so no contour needs explicit on-curve points
+        // other than the start point of each cubic curve
+        let contour = &glyph.contours[0];
+        let on_curve = contour.iter().filter(|point| point.on_curve).count();
+        assert!(on_curve <= 4);
+        assert!(contour.len() > on_curve);
+    }
+
+    #[test]
+    fn closing_points_dropped() {
+        // The explicit line back to the start repeats the start point
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24">
+                <path d="M 4 4 L 20 4 L 20 20 L 4 20 L 4 4 Z"/>
+            </svg>"#;
+        let options = Options::default();
+
+        let icon = simplify_svg(svg.to_string(), &options).expect("failed to simplify SVG");
+        let icon_glyph =
+            create_glyph(icon.bez_paths, icon.view_box, &options).expect("failed to create glyph");
+
+        assert_eq!(num_points(&icon_glyph.glyph), 4);
+    }
+
+    #[test]
+    fn baseline_offset() {
+        let options = Options {
+            vertical_metrics: VerticalMetrics {
+                baseline_offset: -0.2,
+                ..VerticalMetrics::default()
+            },
+            ..Options::default()
+        };
+        let glyph = circle_glyph(24.0, &options);
+
+        assert_eq!((glyph.bbox.y_min, glyph.bbox.y_max), (-200, 800));
+    }
+
+    #[test]
+    fn units_per_em() {
+        let options = Options {
+            units_per_em: 2048,
+            ..Options::default()
+        };
+        let glyph = circle_glyph(24.0, &options);
+
+        assert_eq!(glyph.bbox.bounds(), (0, 0, 2048, 2048));
+    }
+
+    #[test]
+    fn tolerances_independent_of_units_per_em() {
+        // The same tolerance in font units is finer relative to a larger em
+        let large_em = Options {
+            units_per_em: 4096,
+            ..Options::default()
+        };
+        let large_em_scaled_tolerance = Options {
+            curve_tolerance: 0.5 * 4.096,
+            ..large_em.clone()
+        };
+
+        let num_default_points = num_points(&circle_glyph(24.0, &Options::default()));
+        assert!(num_points(&circle_glyph(24.0, &large_em)) > num_default_points);
+        assert_eq!(
+            num_points(&circle_glyph(24.0, &large_em_scaled_tolerance)),
+            num_default_points
+        );
+    }
+
+    #[test]
+    fn coordinate_overflow() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 160 10">
+                <rect width="160" height="10"/>
+            </svg>"#
+            .to_string();
+        let options = Options {
+            units_per_em: 16384,
+            advance_width: AdvanceWidth::Proportional,
+            ..Options::default()
+        };
+
+        let icon = simplify_svg(svg, &options).expect("failed to simplify SVG");
+        let result = create_glyph(icon.bez_paths, icon.view_box, &options);
+
+        assert!(matches!(result, Err(Error::FontUnitsOverflow(_))));
+    }
+
+    #[rstest]
+    #[case(0.0)]
+    #[case(-0.5)]
+    #[case(f64::NAN)]
+    #[case(f64::INFINITY)]
+    fn invalid_scale(#[case] scale: f64) {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24">
+                <rect width="24" height="24"/>
+            </svg>"#
+            .to_string();
+        let options = Options {
+            adjustment: Adjustment {
+                scale,
+                ..Adjustment::default()
+            },
+            ..Options::default()
+        };
+
+        let icon = simplify_svg(svg, &options).expect("failed to simplify SVG");
+        let result = create_glyph(icon.bez_paths, icon.view_box, &options);
+
+        assert!(matches!(result, Err(Error::InvalidScale(_))));
+    }
+
+    #[rstest]
+    #[case(AdvanceWidth::Square, 0.0, 1000, (0, 250, 1000, 750))]
+    #[case(AdvanceWidth::Square, 0.1, 1000, (100, 300, 900, 700))]
+    #[case(AdvanceWidth::Proportional, 0.0, 2000, (0, 0, 2000, 1000))]
+    #[case(AdvanceWidth::Proportional, 0.05, 2100, (50, 0, 2050, 1000))]
+    #[case(AdvanceWidth::ViewBox, 0.0, 2000, (167, 83, 1833, 917))]
+    fn advance_widths(
+        #[case] advance_width: AdvanceWidth,
+        #[case] side_bearing: f64,
+        #[case] expected_advance: u16,
+        #[case] expected_bounds: (i16, i16, i16, i16),
+    ) {
+        // A wide icon with space around it in the view box
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 48 24">
+                <rect x="4" y="2" width="40" height="20"/>
+            </svg>"#
+            .to_string();
+        let options = Options {
+            advance_width,
+            side_bearing,
+            ..Options::default()
+        };
+
+        let icon = simplify_svg(svg, &options).expect("failed to simplify SVG");
+        let icon_glyph =
+            create_glyph(icon.bez_paths, icon.view_box, &options).expect("failed to create glyph");
+
+        assert_eq!(icon_glyph.advance_width, expected_advance);
+        assert_eq!(icon_glyph.glyph.bbox.bounds(), expected_bounds);
+    }
+
+    #[rstest]
+    #[case(Adjustment::default(), (0, 250, 1000, 750))]
+    #[case(Adjustment { padding: 0.1, ..Adjustment::default() }, (100, 300, 900, 700))]
+    #[case(Adjustment { scale: 0.5, ..Adjustment::default() }, (250, 375, 750, 625))]
+    #[case(Adjustment { offset: (0.1, -0.05), ..Adjustment::default() }, (100, 200, 1100, 700))]
+    #[case(Adjustment { fit: Fit::Cover, ..Adjustment::default() }, (-500, 0, 1500, 1000))]
+    #[case(Adjustment { fit: Fit::ViewBox, ..Adjustment::default() }, (83, 292, 917, 708))]
+    fn adjustments(#[case] adjustment: Adjustment, #[case] expected_bounds: (i16, i16, i16, i16)) {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 48 24">
+                <rect x="4" y="2" width="40" height="20"/>
+            </svg>"#
+            .to_string();
+        let options = Options {
+            adjustment,
+            ..Options::default()
+        };
+
+        let icon = simplify_svg(svg, &options).expect("failed to simplify SVG");
+        let icon_glyph =
+            create_glyph(icon.bez_paths, icon.view_box, &options).expect("failed to create glyph");
+
+        assert_eq!(icon_glyph.advance_width, 1000);
+        assert_eq!(icon_glyph.glyph.bbox.bounds(), expected_bounds);
+    }
+
+    #[test]
+    fn remove_exact_midpoints() {
+        let mut curve_points = vec![
+            CurvePoint::on_curve(0, 0),
+            CurvePoint::off_curve(0, 100),
+            CurvePoint::on_curve(50, 100),
+            CurvePoint::off_curve(100, 100),
+            CurvePoint::on_curve(101, 50),
+            CurvePoint::off_curve(100, 0),
+        ];
+        remove_implied_points(&mut curve_points);
+
+        assert_eq!(
+            curve_points,
+            vec![
+                CurvePoint::on_curve(0, 0),
+                CurvePoint::off_curve(0, 100),
+                CurvePoint::off_curve(100, 100),
+                CurvePoint::on_curve(101, 50),
+                CurvePoint::off_curve(100, 0),
+            ]
+        );
+    }
+}
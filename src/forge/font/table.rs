@@ -1,122 +1,1304 @@
 use font_types::{Fixed, LongDateTime};
+use std::{collections::HashMap, iter};
 use write_fonts::{
     tables::{
         cmap::Cmap,
-        glyf::{Bbox, GlyfLocaBuilder, Glyph, SimpleGlyph},
+        glyf::{
+            Anchor, Bbox, Component, ComponentFlags, CompositeGlyph, GlyfLocaBuilder, Glyph,
+            SimpleGlyph, Transform,
+        },
         head::Head,
         hhea::Hhea,
         hmtx::{Hmtx, LongMetric},
+        loca::LocaFormat,
         maxp::Maxp,
+        os2::{Os2, SelectionFlags},
         post::Post,
     },
-    types::GlyphId,
+    types::{GlyphId, GlyphId16, Tag},
     FontBuilder,
 };
 
+mod alias;
+mod colr;
+mod gpos;
+mod gsub;
 mod name;
 
-use self::name::name;
-use super::{glyph::BboxMetrics, ADVANCE};
-use crate::Error;
+use self::{
+    alias::alias_table,
+    colr::{colr_v0, colr_v1, cpal, palette, ColorGlyph},
+    gpos::{gdef, gpos, Attachment},
+    gsub::gsub,
+    name::name,
+};
+use super::{
+    composite::find_compositions,
+    find_duplicates,
+    glyph::{num_points, BboxMetrics, ColorLayer, IconGlyph},
+    to_font_units,
+};
+use crate::{
+    forge::{ColorFormat, Corner, Options, VerticalMetrics},
+    index::ALIAS_TABLE_TAG,
+    Error,
+};
 
 pub fn add_font_tables(
     font: &mut FontBuilder,
     font_name: &str,
-    named_glyphs: &[(String, SimpleGlyph)],
+    named_glyphs: &[(String, IconGlyph)],
+    options: &Options,
 ) -> Result<(), Error> {
-    let num_glyphs = (named_glyphs.len() + 1) as u16; // +1 for .notdef glyph
-    let bbox = named_glyphs
+    let units_per_em = options.units_per_em;
+    if !(16..=16384).contains(&units_per_em) {
+        return Err(Error::FontCreation(format!(
+            "units per em must be between 16 and 16384, got {units_per_em}"
+        )));
+    }
+
+    // Icons with identical glyphs share one glyph, which is named after the first of them
+    let duplicates = find_duplicates(named_glyphs.iter().map(|(_, icon_glyph)| icon_glyph));
+    let mut glyphs: Vec<(&str, &IconGlyph)> = vec![];
+    let mut glyph_indices = Vec::with_capacity(named_glyphs.len());
+    for (index, (name, icon_glyph)) in named_glyphs.iter().enumerate() {
+        if duplicates[index] == index {
+            glyph_indices.push(glyphs.len());
+            glyphs.push((name, icon_glyph));
+        } else {
+            glyph_indices.push(glyph_indices[duplicates[index]]);
+        }
+    }
+    let num_icon_glyphs = glyphs.len();
+
+    // Mirrored glyphs for right-to-left text follow the icons and have no codepoints
+    let mut directional = vec![false; num_icon_glyphs];
+    for (index, (name, _)) in named_glyphs.iter().enumerate() {
+        directional[glyph_indices[index]] |= options.is_directional(name);
+    }
+    let mirrored_glyphs = (0..num_icon_glyphs)
+        .filter(|&glyph_index| directional[glyph_index])
+        .map(|glyph_index| {
+            let (name, icon_glyph) = glyphs[glyph_index];
+            Ok((glyph_index, format!("{name}.rtlm"), icon_glyph.mirrored()?))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    glyphs.extend(
+        mirrored_glyphs
+            .iter()
+            .map(|(_, name, icon_glyph)| (name.as_str(), icon_glyph)),
+    );
+
+    // Marks are attached to a corner of the preceding glyph, mirrored glyphs are marks if their
+    // icons are and attach to the mirrored corner
+    let mut mark_corners: Vec<Option<Corner>> = vec![None; num_icon_glyphs];
+    for (index, (name, _)) in named_glyphs.iter().enumerate() {
+        let mark_corner = &mut mark_corners[glyph_indices[index]];
+        *mark_corner = mark_corner.or_else(|| options.mark_corner(name));
+    }
+    for &(index, ..) in &mirrored_glyphs {
+        mark_corners.push(mark_corners[index].map(Corner::mirrored));
+    }
+    // Marks do not advance the pen, they are positioned relative to the preceding glyph
+    let advance_width = |glyph_index: usize| {
+        if mark_corners[glyph_index].is_some() {
+            0
+        } else {
+            glyphs[glyph_index].1.advance_width
+        }
+    };
+
+    // Icons that contain the outlines of another icon reference its glyph as a component,
+    // the remaining outlines are placed in unencoded glyphs that follow the icons
+    let compositions = if options.composite_glyphs || !options.component_icons.is_empty() {
+        let simple_glyphs: Vec<&SimpleGlyph> = glyphs[..num_icon_glyphs]
+            .iter()
+            .map(|(_, icon_glyph)| &icon_glyph.glyph)
+            .collect();
+        find_compositions(&simple_glyphs, |index, base| {
+            options.reuses_outlines(glyphs[index].0, glyphs[base].0)
+        })
+    } else {
+        vec![None; num_icon_glyphs]
+    };
+    let part_glyphs: Vec<(usize, String, &SimpleGlyph)> = compositions
+        .iter()
+        .enumerate()
+        .filter_map(|(glyph_index, composition)| {
+            let remainder = composition.as_ref()?.remainder.as_ref()?;
+            let name = format!("{}.part", glyphs[glyph_index].0);
+            Some((glyph_index, name, remainder))
+        })
+        .collect();
+    let num_part_glyphs = part_glyphs.len();
+
+    // Layers of color glyphs are placed in unencoded glyphs that follow the mirrored glyphs
+    let layer_glyphs: Vec<(usize, String, &ColorLayer)> = glyphs
+        .iter()
+        .enumerate()
+        .flat_map(|(glyph_index, (name, icon_glyph))| {
+            icon_glyph
+                .layers
+                .iter()
+                .enumerate()
+                .map(move |(layer_index, layer)| {
+                    (glyph_index, format!("{name}.color{layer_index}"), layer)
+                })
+        })
+        .collect();
+
+    // +1 for .notdef glyph
+    let total_glyphs = glyphs.len() + num_part_glyphs + layer_glyphs.len() + 1;
+    let num_glyphs = u16::try_from(total_glyphs).map_err(|_| Error::TooManyGlyphs(total_glyphs))?;
+    // Glyph 0 is .notdef and part glyphs precede the mirrored glyphs
+    let glyph_id = |glyph_index: usize| {
+        if glyph_index < num_icon_glyphs {
+            GlyphId16::new(glyph_index as u16 + 1)
+        } else {
+            GlyphId16::new((glyph_index + num_part_glyphs) as u16 + 1)
+        }
+    };
+    let bbox = glyphs
         .iter()
-        .fold(Bbox::default(), |bbox, (_, glyph)| bbox.union(glyph.bbox));
+        .map(|(_, icon_glyph)| &icon_glyph.glyph)
+        .chain(layer_glyphs.iter().map(|(_, _, layer)| &layer.glyph))
+        .fold(Bbox::default(), |bbox, glyph| bbox.union(glyph.bbox));
+    let advance_width_max = glyphs
+        .iter()
+        .map(|(_, icon_glyph)| icon_glyph.advance_width)
+        .fold(units_per_em, u16::max);
     let (x_min, y_min, x_max, y_max) = bbox.bounds();
 
     // Add name table
     let name_table = name(font_name);
     font.add_table(&name_table)?;
 
-    // Add head table
-    let head_table = head(x_min, y_min, x_max, y_max);
-    font.add_table(&head_table)?;
-
     // Add hhea table
-    let hhea = hhea(num_glyphs);
+    let hhea = hhea(
+        num_glyphs,
+        advance_width_max,
+        options.vertical_metrics,
+        units_per_em,
+    )?;
     font.add_table(&hhea)?;
 
-    // Add maxp table with glyph count
-    let maxp = maxp(num_glyphs);
+    // Add maxp table with glyph count and maximum glyph sizes
+    let simple_glyphs = glyphs
+        .iter()
+        .enumerate()
+        .filter(|&(glyph_index, _)| {
+            compositions
+                .get(glyph_index)
+                .and_then(Option::as_ref)
+                .is_none()
+        })
+        .map(|(_, (_, icon_glyph))| &icon_glyph.glyph)
+        .chain(part_glyphs.iter().map(|&(_, _, glyph)| glyph))
+        .chain(layer_glyphs.iter().map(|(_, _, layer)| &layer.glyph));
+    let composite_glyphs =
+        compositions
+            .iter()
+            .enumerate()
+            .filter_map(|(glyph_index, composition)| {
+                let num_components = 1 + composition.as_ref()?.remainder.iter().count();
+                Some((&glyphs[glyph_index].1.glyph, num_components))
+            });
+    let maxp = maxp(num_glyphs, simple_glyphs, composite_glyphs)?;
     font.add_table(&maxp)?;
 
-    // Add cmap table for Unicode mapping
-    let mappings = named_glyphs.iter().enumerate().map(|(i, _)| {
-        let unicode = char::from_u32(i as u32 + 0xE000).unwrap();
-        (unicode, GlyphId::new(((i + 1) as u16).into()))
-    });
-    let cmap = Cmap::from_mappings(mappings).unwrap();
+    // Add cmap table for Unicode mapping, codepoints in supplementary planes require format 12
+    let icon_ids: Vec<&str> = named_glyphs.iter().map(|(name, _)| name.as_str()).collect();
+    let codepoints = options
+        .codepoint_allocator
+        .allocate(&icon_ids, &options.codepoint_range)?;
+    let mut aliases = vec![];
+    let mappings: Vec<(char, GlyphId)> =
+        cmap_mappings(&icon_ids, codepoints, &options.unicode_codepoints)?
+            .into_iter()
+            .map(|(codepoint, index)| {
+                if duplicates[index] != index {
+                    aliases.push((codepoint, icon_ids[index]));
+                }
+                // Glyph 0 is .notdef
+                (codepoint, GlyphId::new(glyph_indices[index] as u32 + 1))
+            })
+            .collect();
+    // Codepoints beyond the Basic Multilingual Plane are given as 0xFFFF in the OS/2 table
+    let first_char = mappings.iter().map(|&(codepoint, _)| codepoint).min();
+    let last_char = mappings.iter().map(|&(codepoint, _)| codepoint).max();
+    let char_range = (
+        first_char.map_or(0, |codepoint| codepoint.min('\u{FFFF}') as u16),
+        last_char.map_or(0, |codepoint| codepoint.min('\u{FFFF}') as u16),
+    );
+    let cmap =
+        Cmap::from_mappings(mappings).map_err(|error| Error::FontCreation(error.to_string()))?;
     font.add_table(&cmap)?;
 
+    // Add alias table with the names of icons that share their glyph with another icon
+    if !aliases.is_empty() {
+        font.add_raw(ALIAS_TABLE_TAG, alias_table(&aliases)?);
+    }
+
     // Add glyph data
     let mut glyf_builder = GlyfLocaBuilder::new();
 
     // Add .notdef glyph
     glyf_builder.add_glyph(&Glyph::Empty)?;
     let mut glyph_order = vec![".notdef"];
-    let mut h_metrics = vec![LongMetric::new(ADVANCE, 1000)];
-    let mut left_side_bearings = vec![0];
+    let mut h_metrics = vec![LongMetric::new(units_per_em, 0)];
 
-    // Add actual glyphs
-    for (name, glyph) in named_glyphs {
-        glyf_builder.add_glyph(glyph)?;
-        let side_bearing = (ADVANCE.saturating_sub(glyph.bbox.width()) / 2) as i16;
-        h_metrics.push(LongMetric::new(ADVANCE, side_bearing));
-        left_side_bearings.push(side_bearing);
-        glyph_order.push(name.as_str());
+    // Add actual glyphs, the parts of composite glyphs are placed before the mirrored glyphs
+    let mut part_glyph_ids = part_glyphs
+        .iter()
+        .enumerate()
+        .map(|(part_index, _)| GlyphId16::new((num_icon_glyphs + part_index) as u16 + 1));
+    for (glyph_index, &(name, icon_glyph)) in glyphs[..num_icon_glyphs].iter().enumerate() {
+        match &compositions[glyph_index] {
+            Some(composition) => {
+                // Glyph 0 is .notdef
+                let base_glyph = &glyphs[composition.base].1.glyph;
+                let base_id = GlyphId16::new(composition.base as u16 + 1);
+                let (component, bbox) =
+                    offset_component(base_id, composition.offset, base_glyph.bbox);
+                let mut composite = CompositeGlyph::new(component, bbox);
+                if let (Some(remainder), Some(part_id)) =
+                    (&composition.remainder, part_glyph_ids.next())
+                {
+                    let (component, bbox) = offset_component(part_id, (0, 0), remainder.bbox);
+                    composite.add_component(component, bbox);
+                }
+                glyf_builder.add_glyph(&composite)?;
+            }
+            None => {
+                glyf_builder.add_glyph(&icon_glyph.glyph)?;
+            }
+        }
+        // Glyphs are positioned within their advance width, so the side bearing is the left edge
+        h_metrics.push(LongMetric::new(
+            advance_width(glyph_index),
+            icon_glyph.glyph.bbox.x_min,
+        ));
+        glyph_order.push(name);
+    }
+    for (glyph_index, name, glyph) in &part_glyphs {
+        glyf_builder.add_glyph(*glyph)?;
+        h_metrics.push(LongMetric::new(
+            glyphs[*glyph_index].1.advance_width,
+            glyph.bbox.x_min,
+        ));
+        glyph_order.push(name);
+    }
+    for (glyph_index, &(name, icon_glyph)) in glyphs.iter().enumerate().skip(num_icon_glyphs) {
+        glyf_builder.add_glyph(&icon_glyph.glyph)?;
+        h_metrics.push(LongMetric::new(
+            advance_width(glyph_index),
+            icon_glyph.glyph.bbox.x_min,
+        ));
+        glyph_order.push(name);
+    }
+    for (glyph_index, name, layer) in &layer_glyphs {
+        glyf_builder.add_glyph(&layer.glyph)?;
+        h_metrics.push(LongMetric::new(
+            glyphs[*glyph_index].1.advance_width,
+            layer.glyph.bbox.x_min,
+        ));
+        glyph_order.push(name);
     }
 
-    // Add hmtx table
-    let hmtx = Hmtx::new(h_metrics, left_side_bearings);
+    // Add hmtx table, every glyph has a long metric
+    let hmtx = Hmtx::new(h_metrics, vec![]);
     font.add_table(&hmtx)?;
 
     // Add glyf and loca table
-    let (glyf, loca, _loca_format) = glyf_builder.build();
+    let (glyf, loca, loca_format) = glyf_builder.build();
     font.add_table(&glyf)?;
     font.add_table(&loca)?;
 
+    // Add head table, the loca format depends on the size of the glyph data
+    let head_table = head(units_per_em, loca_format, x_min, y_min, x_max, y_max);
+    font.add_table(&head_table)?;
+
     // Add post table
     let post = Post::new_v2(glyph_order);
     font.add_table(&post)?;
 
+    // Number of glyphs that the GSUB and GPOS lookups look at, for the OS/2 table
+    let mut max_context = 0;
+
+    // Add GSUB table that replaces directional icons with their mirrored glyphs in
+    // right-to-left text
+    if !mirrored_glyphs.is_empty() {
+        // Single substitutions replace one glyph
+        max_context = 1;
+        let substitutions = mirrored_glyphs
+            .iter()
+            .enumerate()
+            .map(|(mirrored_index, &(index, _, _))| {
+                // Glyph 0 is .notdef
+                (
+                    GlyphId16::new(index as u16 + 1),
+                    GlyphId16::new((num_icon_glyphs + num_part_glyphs + mirrored_index) as u16 + 1),
+                )
+            })
+            .collect();
+        font.add_table(&gsub(Tag::new(b"rtlm"), substitutions))?;
+    }
+
+    // Add GDEF and GPOS tables that attach marks to the corners of preceding glyphs
+    let mut corners: Vec<Corner> = vec![];
+    for &corner in mark_corners.iter().flatten() {
+        if !corners.contains(&corner) {
+            corners.push(corner);
+        }
+    }
+    if !corners.is_empty() {
+        let attachments: Vec<Attachment> = glyphs
+            .iter()
+            .zip(&mark_corners)
+            .enumerate()
+            .map(|(glyph_index, ((_, icon_glyph), mark_corner))| {
+                let glyph_id = glyph_id(glyph_index);
+                let bbox = icon_glyph.glyph.bbox;
+                match *mark_corner {
+                    Some(corner) => Attachment::Mark(
+                        glyph_id,
+                        corners
+                            .iter()
+                            .position(|&c| c == corner)
+                            .unwrap_or_default() as u16,
+                        corner_point(bbox, corner),
+                    ),
+                    None => Attachment::Base(
+                        glyph_id,
+                        corners
+                            .iter()
+                            .map(|&corner| corner_point(bbox, corner))
+                            .collect(),
+                    ),
+                }
+            })
+            .collect();
+        font.add_table(&gdef(&attachments))?;
+        font.add_table(&gpos(attachments))?;
+        // Mark-to-base attachment looks at the base glyph and the mark
+        max_context = 2;
+    }
+
+    // Add OS/2 table with the same line metrics as the hhea table
+    // The average only includes glyphs with an advance, which excludes marks
+    let advance_widths: Vec<u16> = (0..glyphs.len())
+        .map(advance_width)
+        .filter(|&width| width > 0)
+        .collect();
+    let avg_advance_width = advance_widths
+        .iter()
+        .map(|&width| width as usize)
+        .sum::<usize>()
+        / advance_widths.len().max(1);
+    let os2 = os2(
+        options.vertical_metrics,
+        units_per_em,
+        (y_min, y_max),
+        avg_advance_width as i16,
+        char_range,
+        max_context,
+    )?;
+    font.add_table(&os2)?;
+
+    // Add COLR and CPAL tables that draw icons as stacks of colored layer glyphs, the icon
+    // glyphs remain as monochrome fallback. Gradients require version 1.
+    if !layer_glyphs.is_empty() {
+        let is_colr_v1 = options.color_format == Some(ColorFormat::ColrV1);
+        let first_layer_id = glyphs.len() + num_part_glyphs + 1;
+        let mut color_glyphs: Vec<ColorGlyph> = vec![];
+        for (layer_index, &(glyph_index, _, layer)) in layer_glyphs.iter().enumerate() {
+            let glyph_id = glyph_id(glyph_index);
+            let layer = (
+                GlyphId16::new((first_layer_id + layer_index) as u16),
+                if is_colr_v1 {
+                    layer.paint.clone()
+                } else {
+                    layer.paint.flattened()
+                },
+            );
+            match color_glyphs.last_mut() {
+                Some(color_glyph) if color_glyph.glyph_id == glyph_id => {
+                    color_glyph.layers.push(layer);
+                }
+                _ => color_glyphs.push(ColorGlyph {
+                    glyph_id,
+                    layers: vec![layer],
+                }),
+            }
+        }
+        let palette = palette(&color_glyphs);
+        let colr = if is_colr_v1 {
+            colr_v1(&color_glyphs, &palette)?
+        } else {
+            colr_v0(&color_glyphs, &palette)?
+        };
+        font.add_raw(Tag::new(b"COLR"), colr);
+        font.add_raw(Tag::new(b"CPAL"), cpal(&palette)?);
+    }
+
     Ok(())
 }
 
-pub fn head(x_min: i16, y_min: i16, x_max: i16, y_max: i16) -> Head {
+/// Returns the corner of a bounding box in font units, the y axis points up.
+fn corner_point(bbox: Bbox, corner: Corner) -> (i16, i16) {
+    match corner {
+        Corner::TopLeft => (bbox.x_min, bbox.y_max),
+        Corner::TopRight => (bbox.x_max, bbox.y_max),
+        Corner::BottomLeft => (bbox.x_min, bbox.y_min),
+        Corner::BottomRight => (bbox.x_max, bbox.y_min),
+    }
+}
+
+/// Returns the allocated codepoint and the additional Unicode codepoints of each icon together
+/// with the index of the icon.
+fn cmap_mappings(
+    icon_ids: &[&str],
+    codepoints: Vec<char>,
+    unicode_codepoints: &HashMap<String, Vec<char>>,
+) -> Result<Vec<(char, usize)>, Error> {
+    let mut owners: HashMap<char, usize> = HashMap::new();
+    let mut mappings = Vec::with_capacity(codepoints.len());
+
+    for (index, codepoint) in codepoints.into_iter().enumerate() {
+        let additional_codepoints = unicode_codepoints
+            .get(icon_ids[index])
+            .into_iter()
+            .flatten()
+            .copied();
+        for codepoint in iter::once(codepoint).chain(additional_codepoints) {
+            match owners.get(&codepoint) {
+                Some(&owner) if owner == index => continue,
+                Some(&owner) => {
+                    return Err(Error::CodepointConflict(
+                        codepoint as u32,
+                        icon_ids[owner].to_string(),
+                        icon_ids[index].to_string(),
+                    ));
+                }
+                None => {
+                    owners.insert(codepoint, index);
+                    mappings.push((codepoint, index));
+                }
+            }
+        }
+    }
+
+    Ok(mappings)
+}
+
+pub fn head(
+    units_per_em: u16,
+    loca_format: LocaFormat,
+    x_min: i16,
+    y_min: i16,
+    x_max: i16,
+    y_max: i16,
+) -> Head {
     Head {
         font_revision: Fixed::from_i32(1),
         created: LongDateTime::new(0),
         modified: LongDateTime::new(0),
-        units_per_em: 1000,
+        units_per_em,
         x_min,
         y_min,
         x_max,
         y_max,
-        index_to_loc_format: 0, // Short format
+        index_to_loc_format: loca_format as i16,
         ..Head::default()
     }
 }
 
-pub fn hhea(number_of_long_metrics: u16) -> Hhea {
-    Hhea {
-        ascender: (ADVANCE as i16).into(),
-        descender: 0.into(),
-        line_gap: 0.into(),
-        advance_width_max: ADVANCE.into(),
+pub fn hhea(
+    number_of_long_metrics: u16,
+    advance_width_max: u16,
+    vertical_metrics: VerticalMetrics,
+    units_per_em: u16,
+) -> Result<Hhea, Error> {
+    let em = units_per_em as f64;
+
+    Ok(Hhea {
+        ascender: to_font_units::<i16>(vertical_metrics.ascender * em)?.into(),
+        descender: to_font_units::<i16>(vertical_metrics.descender * em)?.into(),
+        line_gap: to_font_units::<i16>(vertical_metrics.line_gap * em)?.into(),
+        advance_width_max: advance_width_max.into(),
         number_of_long_metrics,
         ..Hhea::default()
-    }
+    })
+}
+
+/// Creates a version 4 `OS/2` table with the vertical metrics and the range of BMP codepoints.
+///
+/// The typographic metrics equal those of the `hhea` table and are preferred by setting
+/// `USE_TYPO_METRICS`. The Windows metrics enclose all glyphs, since Windows clips glyphs at
+/// them. The cap height is the top of the icon area and the x-height is taken from the
+/// vertical metrics. The maximum context is the number of glyphs the lookups look at.
+pub fn os2(
+    vertical_metrics: VerticalMetrics,
+    units_per_em: u16,
+    (y_min, y_max): (i16, i16),
+    avg_advance_width: i16,
+    (first_char, last_char): (u16, u16),
+    max_context: u16,
+) -> Result<Os2, Error> {
+    let em = units_per_em as f64;
+    let ascender = to_font_units::<i16>(vertical_metrics.ascender * em)?;
+    let descender = to_font_units::<i16>(vertical_metrics.descender * em)?;
+    let cap_height = to_font_units::<i16>(
+        (vertical_metrics.baseline_offset + vertical_metrics.icon_height) * em,
+    )?;
+    let win_ascent = ascender.max(y_max).max(0) as u16;
+    let win_descent = (-(descender.min(y_min) as i32)).max(0) as u16;
+
+    Ok(Os2 {
+        x_avg_char_width: avg_advance_width,
+        fs_selection: SelectionFlags::REGULAR | SelectionFlags::USE_TYPO_METRICS,
+        us_first_char_index: first_char,
+        us_last_char_index: last_char,
+        s_typo_ascender: ascender,
+        s_typo_descender: descender,
+        s_typo_line_gap: to_font_units::<i16>(vertical_metrics.line_gap * em)?,
+        us_win_ascent: win_ascent,
+        us_win_descent: win_descent,
+        ul_code_page_range_1: Some(0),
+        ul_code_page_range_2: Some(0),
+        sx_height: Some(to_font_units::<i16>(vertical_metrics.x_height * em)?),
+        s_cap_height: Some(cap_height),
+        us_default_char: Some(0),
+        us_break_char: Some(0x20),
+        us_max_context: Some(max_context),
+        ..Os2::default()
+    })
 }
 
-pub fn maxp(num_glyphs: u16) -> Maxp {
-    Maxp {
+/// Creates a component of a composite glyph that places a glyph at an offset.
+///
+/// Returns the component together with its bounding box after the offset is applied.
+fn offset_component(glyph_id: GlyphId16, (dx, dy): (i16, i16), bbox: Bbox) -> (Component, Bbox) {
+    let component = Component::new(
+        glyph_id,
+        Anchor::Offset { x: dx, y: dy },
+        Transform::default(),
+        ComponentFlags::default(),
+    );
+    let bbox = Bbox {
+        x_min: bbox.x_min + dx,
+        y_min: bbox.y_min + dy,
+        x_max: bbox.x_max + dx,
+        y_max: bbox.y_max + dy,
+    };
+
+    (component, bbox)
+}
+
+/// Creates a version 1.0 `maxp` table as required for TrueType outlines.
+///
+/// Composite glyphs are given by their resolved outlines and their number of components, their
+/// components are simple glyphs.
+pub fn maxp<'a>(
+    num_glyphs: u16,
+    glyphs: impl IntoIterator<Item = &'a SimpleGlyph>,
+    composite_glyphs: impl IntoIterator<Item = (&'a SimpleGlyph, usize)>,
+) -> Result<Maxp, Error> {
+    let mut max_points = 0;
+    let mut max_contours = 0;
+    let mut max_size_of_instructions = 0;
+
+    for glyph in glyphs {
+        max_points = max_points.max(num_points(glyph));
+        max_contours = max_contours.max(glyph.contours.len());
+        max_size_of_instructions = max_size_of_instructions.max(glyph.instructions.len());
+    }
+
+    let mut max_composite_points = 0;
+    let mut max_composite_contours = 0;
+    let mut max_component_elements = 0;
+    for (glyph, num_components) in composite_glyphs {
+        max_composite_points = max_composite_points.max(num_points(glyph));
+        max_composite_contours = max_composite_contours.max(glyph.contours.len());
+        max_component_elements = max_component_elements.max(num_components);
+    }
+    let max_component_depth = u16::from(max_component_elements > 0);
+
+    let to_u16 = |value: usize, what: &str| {
+        u16::try_from(value).map_err(|_| {
+            Error::GlyphConversion(format!(
+                "glyph has {value} {what}, at most 65535 are supported"
+            ))
+        })
+    };
+
+    Ok(Maxp {
         num_glyphs,
-        ..Maxp::default()
+        max_points: Some(to_u16(max_points, "points")?),
+        max_contours: Some(to_u16(max_contours, "contours")?),
+        max_composite_points: Some(to_u16(max_composite_points, "points")?),
+        max_composite_contours: Some(to_u16(max_composite_contours, "contours")?),
+        // No twilight zone is used, but rasterizers commonly expect two zones
+        max_zones: Some(2),
+        max_twilight_points: Some(0),
+        max_storage: Some(0),
+        max_function_defs: Some(0),
+        max_instruction_defs: Some(0),
+        max_stack_elements: Some(0),
+        max_size_of_instructions: Some(to_u16(max_size_of_instructions, "instructions")?),
+        max_component_elements: Some(to_u16(max_component_elements, "components")?),
+        max_component_depth: Some(max_component_depth),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forge::font::test_utils::{build_font_data, icon_glyph, polygon_glyphs, square};
+    use crate::forge::{Color, ColorStop, Extend, Gradient, GradientKind, Paint};
+    use font_types::F2Dot14;
+    use font_types::Version16Dot16;
+    use kurbo::{Affine, Point};
+    use read_fonts::{
+        tables::{
+            cmap::CmapSubtable,
+            colr::{Extend as ReadExtend, Paint as ReadPaint},
+            glyf::{Anchor as ReadAnchor, CurvePoint, Glyph as ReadGlyph},
+            gpos::PositionLookup as ReadPositionLookup,
+            gsub::{SingleSubst, SubstitutionLookup},
+        },
+        FontData, FontRead, FontRef, TableProvider,
+    };
+
+    #[test]
+    fn long_loca_format() {
+        let named_glyphs = polygon_glyphs(4000, 40);
+        let font_data = build_font_data(&named_glyphs, &Options::default()).unwrap();
+
+        let font = FontRef::new(&font_data).unwrap();
+        assert!(font.table_data(Tag::new(b"glyf")).unwrap().len() > 0x20000);
+        assert_eq!(font.head().unwrap().index_to_loc_format(), 1);
+        assert_eq!(font.maxp().unwrap().num_glyphs(), 4001);
+
+        // The last glyph is still found through loca
+        let loca = font.loca(None).unwrap();
+        let last_glyph = loca
+            .get_glyf(GlyphId::new(4000), &font.glyf().unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(last_glyph.number_of_contours(), 1);
+        let cmap = font.cmap().unwrap();
+        assert_eq!(
+            cmap.map_codepoint(0xE000_u32 + 3999),
+            Some(GlyphId::new(4000))
+        );
+    }
+
+    #[test]
+    fn supplementary_plane_codepoints() {
+        let named_glyphs = polygon_glyphs(3, 3);
+        let options = Options {
+            codepoint_range: '\u{F0000}'..='\u{10FFFD}',
+            ..Options::default()
+        };
+        let font_data = build_font_data(&named_glyphs, &options).unwrap();
+
+        let cmap = FontRef::new(&font_data).unwrap().cmap().unwrap();
+        assert!(cmap.encoding_records().iter().any(|record| matches!(
+            record.subtable(cmap.offset_data()),
+            Ok(CmapSubtable::Format12(_))
+        )));
+        assert_eq!(cmap.map_codepoint(0xF0002_u32), Some(GlyphId::new(3)));
+        assert_eq!(cmap.map_codepoint(0xE000_u32), None);
+    }
+
+    #[test]
+    fn unicode_codepoints() {
+        let named_glyphs = polygon_glyphs(3, 3);
+        let options = Options {
+            unicode_codepoints: HashMap::from([
+                ("icon-1".to_string(), vec!['→', '⇒']),
+                ("icon-2".to_string(), vec!['✓']),
+            ]),
+            ..Options::default()
+        };
+        let font_data = build_font_data(&named_glyphs, &options).unwrap();
+        let icon_map = crate::index::build_icon_map_from_bytes(&font_data).unwrap();
+        assert_eq!(icon_map.get("icon-1"), Some(&'\u{E001}'));
+        let unicode_map = crate::index::build_unicode_map_from_bytes(&font_data).unwrap();
+        assert_eq!(unicode_map["icon-1"], ['→', '⇒']);
+        assert_eq!(unicode_map["icon-2"], ['✓']);
+        assert!(!unicode_map.contains_key("icon-0"));
+
+        let conflicting_options = Options {
+            unicode_codepoints: HashMap::from([("icon-2".to_string(), vec!['\u{E000}'])]),
+            ..Options::default()
+        };
+        let result = build_font_data(&named_glyphs, &conflicting_options);
+        assert!(matches!(
+            result,
+            Err(Error::CodepointConflict(0xE000, _, _))
+        ));
+    }
+
+    #[test]
+    fn codepoints_exhausted() {
+        let named_glyphs = polygon_glyphs(6401, 3);
+        let result = build_font_data(&named_glyphs, &Options::default());
+
+        assert!(matches!(
+            result,
+            Err(Error::CodepointsExhausted(6401, 6400))
+        ));
+    }
+
+    #[test]
+    fn too_many_glyphs() {
+        // Mirrored glyphs count towards the limit although they have no codepoints
+        let named_glyphs = polygon_glyphs(32768, 3);
+        let options = Options {
+            directional_icons: vec!["*".to_string()],
+            ..Options::default()
+        };
+        let result = build_font_data(&named_glyphs, &options);
+
+        assert!(matches!(result, Err(Error::TooManyGlyphs(65537))));
+    }
+
+    #[test]
+    fn mirrored_directional_icons() {
+        let named_glyphs = polygon_glyphs(3, 3);
+        let options = Options {
+            directional_icons: vec!["icon-2".to_string()],
+            ..Options::default()
+        };
+        let font_data = build_font_data(&named_glyphs, &options).unwrap();
+
+        let font = FontRef::new(&font_data).unwrap();
+        assert_eq!(font.maxp().unwrap().num_glyphs(), 5);
+        assert_eq!(
+            font.post().unwrap().glyph_name(GlyphId16::new(4)),
+            Some("icon-2.rtlm")
+        );
+
+        let gsub = font.gsub().unwrap();
+        let feature_record = &gsub.feature_list().unwrap().feature_records()[0];
+        assert_eq!(feature_record.feature_tag(), Tag::new(b"rtlm"));
+        let SubstitutionLookup::Single(lookup) =
+            gsub.lookup_list().unwrap().lookups().get(0).unwrap()
+        else {
+            panic!("expected single substitution");
+        };
+        let SingleSubst::Format2(subst) = lookup.subtables().get(0).unwrap() else {
+            panic!("expected single substitution format 2");
+        };
+        assert_eq!(subst.coverage().unwrap().get(GlyphId16::new(3)), Some(0));
+        assert_eq!(subst.substitute_glyph_ids()[0].get(), GlyphId16::new(4));
+        assert_eq!(font.os2().unwrap().us_max_context(), Some(1));
+
+        // The mirrored glyph has no codepoint of its own
+        let icon_map = crate::index::build_icon_map_from_bytes(&font_data).unwrap();
+        assert!(icon_map.contains_key("icon-2"));
+        assert!(!icon_map.contains_key("icon-2.rtlm"));
+    }
+
+    #[test]
+    fn merged_duplicates() {
+        let mut named_glyphs = polygon_glyphs(2, 3);
+        named_glyphs.push(("alias".to_string(), named_glyphs[0].1.clone()));
+        let font_data = build_font_data(&named_glyphs, &Options::default()).unwrap();
+
+        let font = FontRef::new(&font_data).unwrap();
+        assert_eq!(font.maxp().unwrap().num_glyphs(), 3);
+        let cmap = font.cmap().unwrap();
+        assert_eq!(cmap.map_codepoint(0xE000_u32), Some(GlyphId::new(1)));
+        assert_eq!(cmap.map_codepoint(0xE002_u32), Some(GlyphId::new(1)));
+
+        // Each icon keeps its name and codepoint
+        let icon_map = crate::index::build_icon_map_from_bytes(&font_data).unwrap();
+        assert_eq!(icon_map.get("icon-0"), Some(&'\u{E000}'));
+        assert_eq!(icon_map.get("alias"), Some(&'\u{E002}'));
+        assert!(crate::index::build_unicode_map_from_bytes(&font_data)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn composite_glyphs() {
+        let named_glyphs = vec![
+            (
+                "file".to_string(),
+                icon_glyph(vec![square(100, 100, 700), square(200, 200, 500)]),
+            ),
+            (
+                "file-plus".to_string(),
+                icon_glyph(vec![
+                    square(50, 100, 700),
+                    square(150, 200, 500),
+                    square(800, 800, 150),
+                ]),
+            ),
+        ];
+        let options = Options {
+            component_icons: HashMap::from([("file-plus".to_string(), vec!["file".to_string()])]),
+            directional_icons: vec!["file".to_string()],
+            ..Options::default()
+        };
+        let font_data = build_font_data(&named_glyphs, &options).unwrap();
+
+        // The part glyph follows the icons and precedes the mirrored glyph
+        let font = FontRef::new(&font_data).unwrap();
+        let post = font.post().unwrap();
+        assert_eq!(post.glyph_name(GlyphId16::new(3)), Some("file-plus.part"));
+        assert_eq!(post.glyph_name(GlyphId16::new(4)), Some("file.rtlm"));
+
+        let glyf = font.glyf().unwrap();
+        let loca = font.loca(None).unwrap();
+        let Some(ReadGlyph::Composite(composite)) = loca.get_glyf(GlyphId::new(2), &glyf).unwrap()
+        else {
+            panic!("expected composite glyph");
+        };
+        let components: Vec<_> = composite
+            .components()
+            .map(|component| (component.glyph, component.anchor))
+            .collect();
+        assert_eq!(
+            components,
+            [
+                (GlyphId16::new(1), ReadAnchor::Offset { x: -50, y: 0 }),
+                (GlyphId16::new(3), ReadAnchor::Offset { x: 0, y: 0 }),
+            ]
+        );
+        assert_eq!(font.hmtx().unwrap().side_bearing(GlyphId::new(2)), Some(50));
+
+        let maxp = font.maxp().unwrap();
+        assert_eq!(maxp.max_points(), Some(8));
+        assert_eq!(maxp.max_composite_points(), Some(12));
+        assert_eq!(maxp.max_composite_contours(), Some(3));
+        assert_eq!(maxp.max_component_elements(), Some(2));
+        assert_eq!(maxp.max_component_depth(), Some(1));
+    }
+
+    #[test]
+    fn mark_attachment() {
+        let named_glyphs = polygon_glyphs(3, 3);
+        let options = Options {
+            mark_icons: vec![("icon-2".to_string(), Corner::TopRight)],
+            ..Options::default()
+        };
+        let font_data = build_font_data(&named_glyphs, &options).unwrap();
+
+        let font = FontRef::new(&font_data).unwrap();
+        // Mark attachment looks at the base glyph and the mark
+        assert_eq!(font.os2().unwrap().us_max_context(), Some(2));
+        let glyph_classes = font.gdef().unwrap().glyph_class_def().unwrap().unwrap();
+        assert_eq!(glyph_classes.get(GlyphId16::new(1)), 1);
+        assert_eq!(glyph_classes.get(GlyphId16::new(3)), 3);
+
+        let gpos = font.gpos().unwrap();
+        let feature_record = &gpos.feature_list().unwrap().feature_records()[0];
+        assert_eq!(feature_record.feature_tag(), Tag::new(b"mark"));
+        let ReadPositionLookup::MarkToBase(lookup) =
+            gpos.lookup_list().unwrap().lookups().get(0).unwrap()
+        else {
+            panic!("expected mark-to-base positioning");
+        };
+        let subtable = lookup.subtables().get(0).unwrap();
+        assert_eq!(subtable.mark_class_count(), 1);
+        assert_eq!(subtable.base_coverage().unwrap().iter().count(), 2);
+        assert_eq!(
+            subtable.mark_coverage().unwrap().get(GlyphId16::new(3)),
+            Some(0)
+        );
+
+        // The mark is anchored at the top right corner of its bounds
+        let mark_array = subtable.mark_array().unwrap();
+        let mark_anchor = mark_array.mark_records()[0]
+            .mark_anchor(mark_array.offset_data())
+            .unwrap();
+        let bbox = named_glyphs[2].1.glyph.bbox;
+        assert_eq!(
+            (mark_anchor.x_coordinate(), mark_anchor.y_coordinate()),
+            (bbox.x_max, bbox.y_max)
+        );
+
+        // Marks have no advance width
+        let hmtx = font.hmtx().unwrap();
+        assert_eq!(hmtx.advance(GlyphId::new(1)), Some(1000));
+        assert_eq!(hmtx.advance(GlyphId::new(3)), Some(0));
+    }
+
+    #[test]
+    fn mirrored_mark_attachment() {
+        let named_glyphs = polygon_glyphs(2, 3);
+        let options = Options {
+            directional_icons: vec!["icon-1".to_string()],
+            mark_icons: vec![("icon-1".to_string(), Corner::TopRight)],
+            ..Options::default()
+        };
+        let font_data = build_font_data(&named_glyphs, &options).unwrap();
+
+        let font = FontRef::new(&font_data).unwrap();
+        assert_eq!(font.hmtx().unwrap().advance(GlyphId::new(3)), Some(0));
+        let gpos = font.gpos().unwrap();
+        let ReadPositionLookup::MarkToBase(lookup) =
+            gpos.lookup_list().unwrap().lookups().get(0).unwrap()
+        else {
+            panic!("expected mark-to-base positioning");
+        };
+        let subtable = lookup.subtables().get(0).unwrap();
+        assert_eq!(subtable.mark_class_count(), 2);
+
+        // The mirrored mark is anchored at its top left corner, which is the mirrored top right
+        // corner of the mark, and attaches to the top left corner of the preceding glyph
+        let mark_index = subtable
+            .mark_coverage()
+            .unwrap()
+            .get(GlyphId16::new(3))
+            .unwrap();
+        let mark_array = subtable.mark_array().unwrap();
+        let mark_record = &mark_array.mark_records()[mark_index as usize];
+        assert_eq!(mark_record.mark_class(), 1);
+        let mark_anchor = mark_record.mark_anchor(mark_array.offset_data()).unwrap();
+        let icon_glyph = &named_glyphs[1].1;
+        assert_eq!(
+            (mark_anchor.x_coordinate(), mark_anchor.y_coordinate()),
+            (
+                icon_glyph.advance_width as i16 - icon_glyph.glyph.bbox.x_max,
+                icon_glyph.glyph.bbox.y_max
+            )
+        );
+    }
+
+    #[test]
+    fn color_layers() {
+        let mut named_glyphs = polygon_glyphs(2, 8);
+        let red = Color {
+            red: 0xFF,
+            green: 0,
+            blue: 0,
+            alpha: 0x80,
+        };
+        let outline = named_glyphs[1].1.glyph.clone();
+        named_glyphs[1].1.layers = vec![
+            ColorLayer {
+                glyph: outline.clone(),
+                paint: Paint::Solid(red),
+            },
+            ColorLayer {
+                glyph: outline,
+                paint: Paint::FOREGROUND,
+            },
+        ];
+        let font_data = build_font_data(&named_glyphs, &Options::default()).unwrap();
+
+        let font = FontRef::new(&font_data).unwrap();
+        assert_eq!(font.maxp().unwrap().num_glyphs(), 5);
+        let post = font.post().unwrap();
+        assert_eq!(post.glyph_name(GlyphId16::new(3)), Some("icon-1.color0"));
+        assert_eq!(post.glyph_name(GlyphId16::new(4)), Some("icon-1.color1"));
+
+        let colr = font.colr().unwrap();
+        assert_eq!(colr.version(), 0);
+        let base_glyphs = colr.base_glyph_records().unwrap().unwrap();
+        assert_eq!(base_glyphs.len(), 1);
+        assert_eq!(base_glyphs[0].glyph_id(), GlyphId16::new(2));
+        assert_eq!(base_glyphs[0].num_layers(), 2);
+        let layers: Vec<(GlyphId16, u16)> = colr
+            .layer_records()
+            .unwrap()
+            .unwrap()
+            .iter()
+            .map(|layer| (layer.glyph_id(), layer.palette_index()))
+            .collect();
+        assert_eq!(
+            layers,
+            [(GlyphId16::new(3), 0), (GlyphId16::new(4), 0xFFFF)]
+        );
+
+        let cpal = font.cpal().unwrap();
+        assert_eq!(cpal.num_palettes(), 1);
+        let color = cpal.color_records_array().unwrap().unwrap()[0];
+        assert_eq!(
+            (color.red(), color.green(), color.blue(), color.alpha()),
+            (0xFF, 0, 0, 0x80)
+        );
+    }
+
+    #[test]
+    fn gradient_layers() {
+        let mut named_glyphs = polygon_glyphs(1, 8);
+        let gradient = Gradient {
+            kind: GradientKind::Linear {
+                start: Point::new(0.0, 0.0),
+                end: Point::new(100.0, 0.0),
+            },
+            stops: vec![
+                ColorStop {
+                    offset: 0.0,
+                    color: Color {
+                        red: 0xFF,
+                        green: 0,
+                        blue: 0,
+                        alpha: 0xFF,
+                    },
+                    foreground: false,
+                },
+                ColorStop {
+                    offset: 1.0,
+                    color: Color {
+                        red: 0,
+                        green: 0,
+                        blue: 0xFF,
+                        alpha: 0xFF,
+                    },
+                    foreground: false,
+                },
+                ColorStop {
+                    offset: 1.0,
+                    color: Color {
+                        red: 0,
+                        green: 0,
+                        blue: 0,
+                        alpha: 0x80,
+                    },
+                    foreground: true,
+                },
+            ],
+            extend: Extend::Reflect,
+            transform: Affine::new([10.0, 0.0, 0.0, -10.0, 100.0, 900.0]),
+        };
+        let outline = named_glyphs[0].1.glyph.clone();
+        named_glyphs[0].1.layers = vec![
+            ColorLayer {
+                glyph: outline.clone(),
+                paint: Paint::Gradient(gradient),
+            },
+            ColorLayer {
+                glyph: outline,
+                paint: Paint::Foreground { alpha: 0x80 },
+            },
+        ];
+        let options = Options {
+            color_format: Some(ColorFormat::ColrV1),
+            ..Options::default()
+        };
+        let font_data = build_font_data(&named_glyphs, &options).unwrap();
+
+        let font = FontRef::new(&font_data).unwrap();
+        let colr = font.colr().unwrap();
+        assert_eq!(colr.version(), 1);
+        let base_glyph_list = colr.base_glyph_list().unwrap().unwrap();
+        let base_glyph = &base_glyph_list.base_glyph_paint_records()[0];
+        assert_eq!(base_glyph.glyph_id(), GlyphId16::new(1));
+        let ReadPaint::ColrLayers(colr_layers) =
+            base_glyph.paint(base_glyph_list.offset_data()).unwrap()
+        else {
+            panic!("expected layers");
+        };
+        assert_eq!(colr_layers.num_layers(), 2);
+        let layer_list = colr.layer_list().unwrap().unwrap();
+        let paints: Vec<ReadPaint> = layer_list.paints().iter().map(Result::unwrap).collect();
+
+        // Gradient coordinates are scaled to font units
+        let ReadPaint::Glyph(paint_glyph) = &paints[0] else {
+            panic!("expected glyph");
+        };
+        assert_eq!(paint_glyph.glyph_id(), GlyphId16::new(2));
+        let ReadPaint::Transform(paint_transform) = paint_glyph.paint().unwrap() else {
+            panic!("expected transform");
+        };
+        let transform = paint_transform.transform().unwrap();
+        assert_eq!(
+            [
+                transform.xx(),
+                transform.yy(),
+                transform.dx(),
+                transform.dy()
+            ],
+            [1.0, -1.0, 100.0, 900.0].map(Fixed::from_f64)
+        );
+        let ReadPaint::LinearGradient(linear_gradient) = paint_transform.paint().unwrap() else {
+            panic!("expected linear gradient");
+        };
+        let points = [
+            linear_gradient.x0(),
+            linear_gradient.y0(),
+            linear_gradient.x1(),
+            linear_gradient.y1(),
+            linear_gradient.x2(),
+            linear_gradient.y2(),
+        ]
+        .map(|value| value.to_i16());
+        assert_eq!(points, [0, 0, 1000, 0, 0, 1000]);
+        let color_line = linear_gradient.color_line().unwrap();
+        assert_eq!(color_line.extend(), ReadExtend::Reflect);
+        let stops: Vec<(F2Dot14, u16, F2Dot14)> = color_line
+            .color_stops()
+            .iter()
+            .map(|stop| (stop.stop_offset(), stop.palette_index(), stop.alpha()))
+            .collect();
+        // Stops with the foreground color keep their alpha
+        let opaque = F2Dot14::from_f32(1.0);
+        assert_eq!(
+            stops,
+            [
+                (F2Dot14::from_f32(0.0), 0, opaque),
+                (F2Dot14::from_f32(1.0), 1, opaque),
+                (
+                    F2Dot14::from_f32(1.0),
+                    0xFFFF,
+                    F2Dot14::from_f32(128.0 / 255.0)
+                ),
+            ]
+        );
+
+        // The foreground color keeps its alpha
+        let ReadPaint::Glyph(paint_glyph) = &paints[1] else {
+            panic!("expected glyph");
+        };
+        let ReadPaint::Solid(solid) = paint_glyph.paint().unwrap() else {
+            panic!("expected solid");
+        };
+        assert_eq!(solid.palette_index(), 0xFFFF);
+        assert_eq!(solid.alpha(), F2Dot14::from_f32(128.0 / 255.0));
+    }
+
+    #[test]
+    fn gradient_coordinates_clamped() {
+        let mut named_glyphs = polygon_glyphs(1, 8);
+        let red = Color {
+            red: 0xFF,
+            green: 0,
+            blue: 0,
+            alpha: 0xFF,
+        };
+        let gradient = Gradient {
+            kind: GradientKind::Radial {
+                start: Point::new(-1e6, 0.0),
+                start_radius: -1.0,
+                end: Point::new(1e6, 0.0),
+                end_radius: 1e6,
+            },
+            stops: vec![
+                ColorStop {
+                    offset: 0.0,
+                    color: red,
+                    foreground: false,
+                },
+                ColorStop {
+                    offset: 1.0,
+                    color: red,
+                    foreground: false,
+                },
+            ],
+            extend: Extend::Pad,
+            transform: Affine::IDENTITY,
+        };
+        named_glyphs[0].1.layers = vec![ColorLayer {
+            glyph: named_glyphs[0].1.glyph.clone(),
+            paint: Paint::Gradient(gradient),
+        }];
+        let options = Options {
+            color_format: Some(ColorFormat::ColrV1),
+            ..Options::default()
+        };
+        let font_data = build_font_data(&named_glyphs, &options).unwrap();
+
+        let font = FontRef::new(&font_data).unwrap();
+        let layer_list = font.colr().unwrap().layer_list().unwrap().unwrap();
+        let ReadPaint::Glyph(paint_glyph) = layer_list.paints().get(0).unwrap() else {
+            panic!("expected glyph");
+        };
+        let ReadPaint::Transform(paint_transform) = paint_glyph.paint().unwrap() else {
+            panic!("expected transform");
+        };
+        let ReadPaint::RadialGradient(radial_gradient) = paint_transform.paint().unwrap() else {
+            panic!("expected radial gradient");
+        };
+        assert_eq!(
+            [radial_gradient.x0().to_i16(), radial_gradient.x1().to_i16()],
+            [i16::MIN, i16::MAX]
+        );
+        assert_eq!(
+            [
+                radial_gradient.radius0().to_u16(),
+                radial_gradient.radius1().to_u16()
+            ],
+            [0, u16::MAX]
+        );
+    }
+
+    #[test]
+    fn os2_metrics() {
+        let named_glyphs = polygon_glyphs(2, 8);
+        let options = Options {
+            units_per_em: 2000,
+            vertical_metrics: VerticalMetrics::text_aligned(),
+            ..Options::default()
+        };
+        let font_data = build_font_data(&named_glyphs, &options).unwrap();
+
+        let font = FontRef::new(&font_data).unwrap();
+        let hhea = font.hhea().unwrap();
+        let os2 = font.os2().unwrap();
+        assert!(os2
+            .fs_selection()
+            .contains(read_fonts::tables::os2::SelectionFlags::USE_TYPO_METRICS));
+        assert_eq!(os2.s_typo_ascender(), hhea.ascender().to_i16());
+        assert_eq!(os2.s_typo_descender(), hhea.descender().to_i16());
+        assert_eq!(os2.s_typo_line_gap(), hhea.line_gap().to_i16());
+        assert_eq!(
+            (os2.s_typo_ascender(), os2.s_typo_descender()),
+            (1900, -500)
+        );
+        // Windows metrics enclose the glyphs
+        assert!(os2.us_win_ascent() as i16 >= font.head().unwrap().y_max());
+        assert_eq!(os2.us_win_descent(), 500);
+        // The cap height is the top of the icon area
+        assert_eq!(os2.s_cap_height(), Some(1615));
+        assert_eq!(os2.sx_height(), Some(1040));
+        // Without GSUB and GPOS lookups there is no context
+        assert_eq!(os2.us_max_context(), Some(0));
+        assert_eq!(
+            (os2.us_first_char_index(), os2.us_last_char_index()),
+            (0xE000, 0xE001)
+        );
+    }
+
+    #[test]
+    fn maxp_statistics() {
+        let triangle = vec![
+            CurvePoint::on_curve(0, 0),
+            CurvePoint::on_curve(100, 0),
+            CurvePoint::on_curve(50, 100),
+        ];
+        let square = vec![
+            CurvePoint::on_curve(0, 0),
+            CurvePoint::on_curve(100, 0),
+            CurvePoint::on_curve(100, 100),
+            CurvePoint::on_curve(0, 100),
+        ];
+        let glyphs = [
+            SimpleGlyph {
+                bbox: Bbox::default(),
+                contours: vec![square.clone().into()],
+                instructions: vec![],
+            },
+            SimpleGlyph {
+                bbox: Bbox::default(),
+                contours: vec![triangle.clone().into(), triangle.into()],
+                instructions: vec![],
+            },
+        ];
+
+        let data = write_fonts::dump_table(&maxp(3, &glyphs, []).unwrap()).unwrap();
+        let maxp = read_fonts::tables::maxp::Maxp::read(FontData::new(&data)).unwrap();
+
+        assert_eq!(maxp.version(), Version16Dot16::VERSION_1_0);
+        assert_eq!(maxp.num_glyphs(), 3);
+        assert_eq!(maxp.max_points(), Some(6));
+        assert_eq!(maxp.max_contours(), Some(2));
+        assert_eq!(maxp.max_component_depth(), Some(0));
     }
 }
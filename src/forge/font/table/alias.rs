@@ -0,0 +1,53 @@
+use crate::{index::ALIAS_TABLE_VERSION, Error};
+
+/// Creates the data of the alias table, see
+/// [`ALIAS_TABLE_TAG`](crate::index::ALIAS_TABLE_TAG).
+pub fn alias_table(aliases: &[(char, &str)]) -> Result<Vec<u8>, Error> {
+    let too_long = || Error::FontCreation("too many or too long icon aliases".into());
+
+    let mut data = vec![];
+    data.extend(ALIAS_TABLE_VERSION.to_be_bytes());
+    data.extend(
+        u16::try_from(aliases.len())
+            .map_err(|_| too_long())?
+            .to_be_bytes(),
+    );
+    for &(codepoint, name) in aliases {
+        data.extend((codepoint as u32).to_be_bytes());
+        data.extend(
+            u16::try_from(name.len())
+                .map_err(|_| too_long())?
+                .to_be_bytes(),
+        );
+        data.extend(name.as_bytes());
+    }
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::read_aliases;
+    use std::collections::HashMap;
+
+    #[test]
+    fn round_trip() {
+        let data = alias_table(&[('\u{E002}', "alias"), ('\u{F0000}', "café")]).unwrap();
+
+        assert_eq!(
+            read_aliases(&data),
+            HashMap::from([(0xE002, "alias".to_string()), (0xF0000, "café".to_string())])
+        );
+        // A truncated table keeps its complete records
+        assert_eq!(read_aliases(&data[..data.len() - 1]).len(), 1);
+    }
+
+    #[test]
+    fn other_versions_ignored() {
+        let mut data = alias_table(&[('\u{E002}', "alias")]).unwrap();
+        data[..2].copy_from_slice(&(ALIAS_TABLE_VERSION + 1).to_be_bytes());
+
+        assert!(read_aliases(&data).is_empty());
+    }
+}
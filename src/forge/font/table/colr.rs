@@ -0,0 +1,345 @@
+use kurbo::Affine;
+use write_fonts::types::{F2Dot14, Fixed, GlyphId16};
+
+use crate::{
+    forge::{
+        color::{ColorStop, Extend, Gradient, GradientKind},
+        Color, Paint,
+    },
+    Error,
+};
+
+/// Palette index that stands for the color of the surrounding text
+const FOREGROUND_PALETTE_INDEX: u16 = 0xFFFF;
+
+/// Size of the header of a version 1 `COLR` table
+const COLR_V1_HEADER_SIZE: u32 = 34;
+
+/// Glyph that is drawn as a stack of layer glyphs in color glyph rendering
+pub struct ColorGlyph {
+    pub glyph_id: GlyphId16,
+    /// Layer glyphs from bottom to top
+    pub layers: Vec<(GlyphId16, Paint)>,
+}
+
+/// Returns the distinct colors of the layers and their gradient stops in the order of their
+/// first use.
+pub fn palette(color_glyphs: &[ColorGlyph]) -> Vec<Color> {
+    let mut colors = vec![];
+    for color_glyph in color_glyphs {
+        for (_, paint) in &color_glyph.layers {
+            let paint_colors = match paint {
+                Paint::Foreground { .. } => vec![],
+                Paint::Solid(color) => vec![*color],
+                Paint::Gradient(gradient) => gradient
+                    .stops
+                    .iter()
+                    .filter(|stop| !stop.foreground)
+                    .map(|stop| stop.color)
+                    .collect(),
+            };
+            for color in paint_colors {
+                if !colors.contains(&color) {
+                    colors.push(color);
+                }
+            }
+        }
+    }
+
+    colors
+}
+
+/// Creates the data of a version 0 `COLR` table with layers that refer to the palette.
+pub fn colr_v0(color_glyphs: &[ColorGlyph], palette: &[Color]) -> Result<Vec<u8>, Error> {
+    let too_many = || Error::FontCreation("too many color layers".into());
+
+    // Base glyph records are searched by glyph ID
+    let mut color_glyphs: Vec<&ColorGlyph> = color_glyphs.iter().collect();
+    color_glyphs.sort_unstable_by_key(|color_glyph| color_glyph.glyph_id);
+
+    let num_base_glyphs = u16::try_from(color_glyphs.len()).map_err(|_| too_many())?;
+    let num_layers: usize = color_glyphs
+        .iter()
+        .map(|color_glyph| color_glyph.layers.len())
+        .sum();
+    let num_layers = u16::try_from(num_layers).map_err(|_| too_many())?;
+    let base_glyphs_offset = 14_u32;
+    let layers_offset = base_glyphs_offset + 6 * num_base_glyphs as u32;
+
+    let mut data = vec![];
+    data.extend(0_u16.to_be_bytes());
+    data.extend(num_base_glyphs.to_be_bytes());
+    data.extend(base_glyphs_offset.to_be_bytes());
+    data.extend(layers_offset.to_be_bytes());
+    data.extend(num_layers.to_be_bytes());
+
+    let mut first_layer = 0_u16;
+    for color_glyph in &color_glyphs {
+        // Layer counts fit into 16 bits since their sum does
+        let num_layers = color_glyph.layers.len() as u16;
+        data.extend(color_glyph.glyph_id.to_u16().to_be_bytes());
+        data.extend(first_layer.to_be_bytes());
+        data.extend(num_layers.to_be_bytes());
+        first_layer += num_layers;
+    }
+    for color_glyph in &color_glyphs {
+        for (glyph_id, paint) in &color_glyph.layers {
+            // The alpha of the foreground color cannot be expressed in version 0
+            let palette_index = match paint.flattened() {
+                Paint::Foreground { .. } => FOREGROUND_PALETTE_INDEX,
+                Paint::Solid(color) => palette_index(palette, color)?,
+                Paint::Gradient(_) => unreachable!("gradients are flattened"),
+            };
+            data.extend(glyph_id.to_u16().to_be_bytes());
+            data.extend(palette_index.to_be_bytes());
+        }
+    }
+
+    Ok(data)
+}
+
+/// Creates the data of a version 1 `COLR` table with a paint graph for each color glyph.
+///
+/// Each color glyph paints its layers, which fill their glyph outlines with a solid color or a
+/// gradient. Colors refer to the palette, whose colors are used with full alpha, and the
+/// foreground color keeps its alpha.
+pub fn colr_v1(color_glyphs: &[ColorGlyph], palette: &[Color]) -> Result<Vec<u8>, Error> {
+    let too_many = || Error::FontCreation("too many color layers".into());
+
+    // Base glyph paint records are searched by glyph ID
+    let mut color_glyphs: Vec<&ColorGlyph> = color_glyphs.iter().collect();
+    color_glyphs.sort_unstable_by_key(|color_glyph| color_glyph.glyph_id);
+
+    // Each base glyph paints a slice of the layer list
+    let mut base_glyph_list = vec![];
+    base_glyph_list.extend((color_glyphs.len() as u32).to_be_bytes());
+    let records_size = 4 + 6 * color_glyphs.len();
+    let mut first_layer = 0_u32;
+    for (index, color_glyph) in color_glyphs.iter().enumerate() {
+        let paint_offset = records_size + 6 * index;
+        base_glyph_list.extend(color_glyph.glyph_id.to_u16().to_be_bytes());
+        base_glyph_list.extend((paint_offset as u32).to_be_bytes());
+    }
+    for color_glyph in &color_glyphs {
+        // PaintColrLayers
+        let num_layers = u8::try_from(color_glyph.layers.len()).map_err(|_| {
+            Error::FontCreation(format!(
+                "color glyph has {} layers, at most 255 are supported",
+                color_glyph.layers.len()
+            ))
+        })?;
+        base_glyph_list.push(1);
+        base_glyph_list.push(num_layers);
+        base_glyph_list.extend(first_layer.to_be_bytes());
+        first_layer += num_layers as u32;
+    }
+
+    let mut layer_list = vec![];
+    layer_list.extend(first_layer.to_be_bytes());
+    let mut paints = vec![];
+    let paints_offset = 4 + 4 * first_layer as usize;
+    for color_glyph in &color_glyphs {
+        for (glyph_id, paint) in &color_glyph.layers {
+            let offset = u32::try_from(paints_offset + paints.len()).map_err(|_| too_many())?;
+            layer_list.extend(offset.to_be_bytes());
+            write_paint_glyph(&mut paints, *glyph_id, paint, palette)?;
+        }
+    }
+    layer_list.extend(paints);
+
+    let base_glyph_list_offset = COLR_V1_HEADER_SIZE;
+    let layer_list_offset = u32::try_from(base_glyph_list.len())
+        .ok()
+        .and_then(|size| base_glyph_list_offset.checked_add(size))
+        .ok_or_else(too_many)?;
+
+    let mut data = vec![];
+    data.extend(1_u16.to_be_bytes());
+    // No base glyph and layer records of version 0
+    data.extend(0_u16.to_be_bytes());
+    data.extend(0_u32.to_be_bytes());
+    data.extend(0_u32.to_be_bytes());
+    data.extend(0_u16.to_be_bytes());
+    data.extend(base_glyph_list_offset.to_be_bytes());
+    data.extend(layer_list_offset.to_be_bytes());
+    // No clip list, variation index map and item variation store
+    data.extend([0; 12]);
+    data.extend(base_glyph_list);
+    data.extend(layer_list);
+
+    Ok(data)
+}
+
+/// Writes a `PaintGlyph` table that fills the outlines of a glyph, followed by its paint.
+fn write_paint_glyph(
+    data: &mut Vec<u8>,
+    glyph_id: GlyphId16,
+    paint: &Paint,
+    palette: &[Color],
+) -> Result<(), Error> {
+    data.push(10);
+    write_offset24(data, 6);
+    data.extend(glyph_id.to_u16().to_be_bytes());
+
+    match paint {
+        Paint::Foreground { alpha } => {
+            write_paint_solid(data, FOREGROUND_PALETTE_INDEX, *alpha);
+        }
+        Paint::Solid(color) => {
+            write_paint_solid(data, palette_index(palette, *color)?, 0xFF);
+        }
+        Paint::Gradient(gradient) => write_paint_gradient(data, gradient, palette)?,
+    }
+
+    Ok(())
+}
+
+fn write_paint_solid(data: &mut Vec<u8>, palette_index: u16, alpha: u8) {
+    data.push(2);
+    data.extend(palette_index.to_be_bytes());
+    data.extend(to_f2dot14(alpha as f32 / 255.0).to_be_bytes());
+}
+
+/// Writes a `PaintTransform` table followed by its transform and the gradient.
+///
+/// The gradient is scaled to font units, so that its coordinates can be rounded, and the
+/// transform keeps the remaining rotation, skew and offset. Coordinates beyond the range of
+/// font units are clamped, which only affects gradients that extend far beyond the glyph.
+fn write_paint_gradient(
+    data: &mut Vec<u8>,
+    gradient: &Gradient,
+    palette: &[Color],
+) -> Result<(), Error> {
+    let [xx, yx, xy, yy, dx, dy] = gradient.transform.as_coeffs();
+    let scale = (xx * yy - yx * xy).abs().sqrt();
+    if scale < f64::EPSILON || gradient.stops.is_empty() {
+        // A degenerate transform squeezes the gradient into a line, SVG renders it in the
+        // color of its last stop and gradients without stops not at all
+        match gradient.stops.last() {
+            Some(stop) => {
+                let (palette_index, alpha) = stop_color(palette, stop)?;
+                write_paint_solid(data, palette_index, alpha);
+            }
+            None => write_paint_solid(data, FOREGROUND_PALETTE_INDEX, 0),
+        }
+        return Ok(());
+    }
+
+    // PaintTransform
+    data.push(12);
+    write_offset24(data, 7 + 24);
+    write_offset24(data, 7);
+    // Affine2x3
+    let affine = Affine::new([xx / scale, yx / scale, xy / scale, yy / scale, dx, dy]);
+    for coefficient in affine.as_coeffs() {
+        data.extend(Fixed::from_f64(coefficient).to_be_bytes());
+    }
+
+    // Float to integer casts saturate
+    let font_units = |value: f64| ((value * scale).round() as i16).to_be_bytes();
+    let radius = |value: f64| ((value * scale).round() as u16).to_be_bytes();
+    match gradient.kind {
+        GradientKind::Linear { start, end } => {
+            // Colors are constant along lines perpendicular to the gradient vector
+            let direction = end - start;
+            let rotation = start + kurbo::Vec2::new(-direction.y, direction.x);
+            data.push(4);
+            write_offset24(data, 16);
+            for point in [start, end, rotation] {
+                data.extend(font_units(point.x));
+                data.extend(font_units(point.y));
+            }
+        }
+        GradientKind::Radial {
+            start,
+            start_radius,
+            end,
+            end_radius,
+        } => {
+            data.push(6);
+            write_offset24(data, 16);
+            data.extend(font_units(start.x));
+            data.extend(font_units(start.y));
+            data.extend(radius(start_radius));
+            data.extend(font_units(end.x));
+            data.extend(font_units(end.y));
+            data.extend(radius(end_radius));
+        }
+    }
+
+    write_color_line(data, &gradient.stops, gradient.extend, palette)
+}
+
+fn write_color_line(
+    data: &mut Vec<u8>,
+    stops: &[ColorStop],
+    extend: Extend,
+    palette: &[Color],
+) -> Result<(), Error> {
+    let num_stops = u16::try_from(stops.len())
+        .map_err(|_| Error::FontCreation("too many gradient stops".into()))?;
+
+    data.push(match extend {
+        Extend::Pad => 0,
+        Extend::Repeat => 1,
+        Extend::Reflect => 2,
+    });
+    data.extend(num_stops.to_be_bytes());
+    for stop in stops {
+        let (palette_index, alpha) = stop_color(palette, stop)?;
+        data.extend(to_f2dot14(stop.offset).to_be_bytes());
+        data.extend(palette_index.to_be_bytes());
+        data.extend(to_f2dot14(alpha as f32 / 255.0).to_be_bytes());
+    }
+
+    Ok(())
+}
+
+/// Returns the palette index and the alpha of a gradient stop.
+///
+/// Colors of the palette include their alpha, the foreground color keeps the alpha of the stop.
+fn stop_color(palette: &[Color], stop: &ColorStop) -> Result<(u16, u8), Error> {
+    if stop.foreground {
+        Ok((FOREGROUND_PALETTE_INDEX, stop.color.alpha))
+    } else {
+        Ok((palette_index(palette, stop.color)?, 0xFF))
+    }
+}
+
+fn write_offset24(data: &mut Vec<u8>, offset: u32) {
+    data.extend(&offset.to_be_bytes()[1..]);
+}
+
+fn to_f2dot14(value: f32) -> F2Dot14 {
+    F2Dot14::from_f32(value)
+}
+
+/// Creates the data of a version 0 `CPAL` table with a single palette.
+pub fn cpal(palette: &[Color]) -> Result<Vec<u8>, Error> {
+    let num_colors = u16::try_from(palette.len())
+        .ok()
+        .filter(|&num_colors| num_colors < FOREGROUND_PALETTE_INDEX)
+        .ok_or_else(|| Error::FontCreation("too many colors in the palette".into()))?;
+
+    let mut data = vec![];
+    data.extend(0_u16.to_be_bytes());
+    data.extend(num_colors.to_be_bytes());
+    // One palette whose colors start at the first color record
+    data.extend(1_u16.to_be_bytes());
+    data.extend(num_colors.to_be_bytes());
+    data.extend(14_u32.to_be_bytes());
+    data.extend(0_u16.to_be_bytes());
+    for color in palette {
+        data.extend([color.blue, color.green, color.red, color.alpha]);
+    }
+
+    Ok(data)
+}
+
+fn palette_index(palette: &[Color], color: Color) -> Result<u16, Error> {
+    palette
+        .iter()
+        .position(|&other| other == color)
+        .map(|index| index as u16)
+        .ok_or_else(|| Error::FontCreation("color is missing from the palette".into()))
+}
@@ -0,0 +1,88 @@
+use write_fonts::{
+    tables::{
+        gdef::{Gdef, GlyphClassDef},
+        gpos::{
+            AnchorTable, BaseArray, BaseRecord, Gpos, MarkArray, MarkBasePosFormat1, MarkRecord,
+            PositionLookup, PositionLookupList,
+        },
+        layout::{
+            ClassDef, CoverageTable, Feature, FeatureList, FeatureRecord, LangSys, Lookup,
+            LookupFlag, Script, ScriptList, ScriptRecord,
+        },
+    },
+    types::{GlyphId16, Tag},
+};
+
+/// Glyph that takes part in mark attachment
+pub enum Attachment {
+    /// Glyph that marks attach to, with an anchor for each mark class
+    Base(GlyphId16, Vec<(i16, i16)>),
+    /// Glyph that is attached to a preceding base glyph by the anchor of its mark class
+    Mark(GlyphId16, u16, (i16, i16)),
+}
+
+/// Creates a `GDEF` table that classifies glyphs as bases or marks.
+pub fn gdef(attachments: &[Attachment]) -> Gdef {
+    let glyph_classes: ClassDef = attachments
+        .iter()
+        .map(|attachment| match *attachment {
+            Attachment::Base(glyph, _) => (glyph, GlyphClassDef::Base as u16),
+            Attachment::Mark(glyph, ..) => (glyph, GlyphClassDef::Mark as u16),
+        })
+        .collect();
+
+    Gdef::new(Some(glyph_classes), None, None, None)
+}
+
+/// Creates a `GPOS` table with a `mark` feature that attaches marks to base glyphs.
+///
+/// The feature is registered for the default script, which applies to all scripts without
+/// their own entry.
+pub fn gpos(mut attachments: Vec<Attachment>) -> Gpos {
+    // Records are listed in the order of the coverage tables
+    attachments.sort_unstable_by_key(|attachment| match *attachment {
+        Attachment::Base(glyph, _) | Attachment::Mark(glyph, ..) => glyph,
+    });
+
+    let mut marks = vec![];
+    let mut mark_records = vec![];
+    let mut bases = vec![];
+    let mut base_records = vec![];
+    for attachment in attachments {
+        match attachment {
+            Attachment::Base(glyph, anchors) => {
+                bases.push(glyph);
+                base_records.push(BaseRecord::new(
+                    anchors
+                        .into_iter()
+                        .map(|(x, y)| Some(AnchorTable::format_1(x, y)))
+                        .collect(),
+                ));
+            }
+            Attachment::Mark(glyph, class, (x, y)) => {
+                marks.push(glyph);
+                mark_records.push(MarkRecord::new(class, AnchorTable::format_1(x, y)));
+            }
+        }
+    }
+
+    let lookup = Lookup::new(
+        LookupFlag::empty(),
+        vec![MarkBasePosFormat1::new(
+            marks.into_iter().collect::<CoverageTable>(),
+            bases.into_iter().collect::<CoverageTable>(),
+            MarkArray::new(mark_records),
+            BaseArray::new(base_records),
+        )],
+    );
+
+    let script = Script::new(Some(LangSys::new(vec![0])), vec![]);
+    Gpos::new(
+        ScriptList::new(vec![ScriptRecord::new(Tag::new(b"DFLT"), script)]),
+        FeatureList::new(vec![FeatureRecord::new(
+            Tag::new(b"mark"),
+            Feature::new(None, vec![0]),
+        )]),
+        PositionLookupList::new(vec![PositionLookup::MarkToBase(lookup)]),
+    )
+}
@@ -0,0 +1,38 @@
+use write_fonts::{
+    tables::{
+        gsub::{Gsub, SingleSubst, SubstitutionLookup, SubstitutionLookupList},
+        layout::{
+            CoverageTable, Feature, FeatureList, FeatureRecord, LangSys, Lookup, LookupFlag,
+            Script, ScriptList, ScriptRecord,
+        },
+    },
+    types::{GlyphId16, Tag},
+};
+
+/// Creates a `GSUB` table with a feature that substitutes single glyphs.
+///
+/// The feature is registered for the default script, which applies to all scripts without
+/// their own entry.
+pub fn gsub(feature_tag: Tag, mut substitutions: Vec<(GlyphId16, GlyphId16)>) -> Gsub {
+    // Substitutes are listed in the order of the coverage table
+    substitutions.sort_unstable();
+    let coverage: CoverageTable = substitutions.iter().map(|&(glyph, _)| glyph).collect();
+    let substitutes = substitutions
+        .iter()
+        .map(|&(_, substitute)| substitute)
+        .collect();
+    let lookup = Lookup::new(
+        LookupFlag::empty(),
+        vec![SingleSubst::format_2(coverage, substitutes)],
+    );
+
+    let script = Script::new(Some(LangSys::new(vec![0])), vec![]);
+    Gsub::new(
+        ScriptList::new(vec![ScriptRecord::new(Tag::new(b"DFLT"), script)]),
+        FeatureList::new(vec![FeatureRecord::new(
+            feature_tag,
+            Feature::new(None, vec![0]),
+        )]),
+        SubstitutionLookupList::new(vec![SubstitutionLookup::Single(lookup)]),
+    )
+}
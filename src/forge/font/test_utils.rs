@@ -0,0 +1,73 @@
+//! Glyphs and fonts shared by the tests of the font modules
+
+use read_fonts::tables::glyf::CurvePoint;
+use write_fonts::{
+    tables::glyf::{Bbox, Contour, SimpleGlyph},
+    FontBuilder,
+};
+
+use super::{table::add_font_tables, IconGlyph};
+use crate::{forge::Options, Error};
+
+/// Creates a square contour with its lower left corner at the given point
+pub(super) fn square(x: i16, y: i16, size: i16) -> Contour {
+    vec![
+        CurvePoint::on_curve(x, y),
+        CurvePoint::on_curve(x, y + size),
+        CurvePoint::on_curve(x + size, y + size),
+        CurvePoint::on_curve(x + size, y),
+    ]
+    .into()
+}
+
+/// Creates a glyph of the contours with its bounding box
+pub(super) fn simple_glyph(contours: Vec<Contour>) -> SimpleGlyph {
+    let mut glyph = SimpleGlyph {
+        bbox: Bbox::default(),
+        contours,
+        instructions: vec![],
+    };
+    glyph.recompute_bounding_box();
+    glyph
+}
+
+/// Creates an icon glyph of the contours with an advance width of 1000
+pub(super) fn icon_glyph(contours: Vec<Contour>) -> IconGlyph {
+    IconGlyph {
+        glyph: simple_glyph(contours),
+        advance_width: 1000,
+        layers: vec![],
+    }
+}
+
+/// Creates named glyphs with a polygon of the given number of points each
+pub(super) fn polygon_glyphs(num_glyphs: usize, num_points: usize) -> Vec<(String, IconGlyph)> {
+    (0..num_glyphs)
+        .map(|i| {
+            let contour: Vec<CurvePoint> = (0..num_points)
+                .map(|j| {
+                    let angle = std::f64::consts::TAU * j as f64 / num_points as f64;
+                    // Distinct glyphs are not merged
+                    let radius = 300.0 + (i % 200) as f64;
+                    let offset = (i / 200) as f64;
+                    CurvePoint::on_curve(
+                        (500.0 + offset + radius * angle.cos()) as i16,
+                        (500.0 + radius * angle.sin()) as i16,
+                    )
+                })
+                .collect();
+            (format!("icon-{i}"), icon_glyph(vec![contour.into()]))
+        })
+        .collect()
+}
+
+/// Builds a font named "Icons" of the named glyphs and returns its data
+pub(super) fn build_font_data(
+    named_glyphs: &[(String, IconGlyph)],
+    options: &Options,
+) -> Result<Vec<u8>, Error> {
+    let mut font_builder = FontBuilder::new();
+    add_font_tables(&mut font_builder, "Icons", named_glyphs, options)?;
+
+    Ok(font_builder.build())
+}
@@ -0,0 +1,785 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use toml_edit::{Array, ArrayOfTables, DocumentMut, InlineTable, Item, Table, Value};
+
+use super::{
+    adjustment::is_positive, Adjustment, AdvanceWidth, ComposedIcon, Corner, Fit, Options, Variant,
+    VariantTransform,
+};
+use crate::{index::IconMap, Error};
+
+const ICON_KEYS: [&str; 15] = [
+    "id",
+    "path",
+    "aliases",
+    "codepoint",
+    "unicode",
+    "tags",
+    "advance-width",
+    "padding",
+    "scale",
+    "offset",
+    "fit",
+    "variants",
+    "directional",
+    "components",
+    "mark",
+];
+
+const COMPOSE_KEYS: [&str; 6] = ["id", "base", "badge", "corner", "scale", "gap"];
+
+/// Names of manifest files that [`make_font`](super::make_font) looks for in the source
+/// directory, in order of precedence
+const MANIFEST_FILE_NAMES: [&str; 2] = ["manifest.toml", "manifest.json"];
+
+/// Description of icons in a TOML or JSON file.
+///
+/// Each icon is described by an `[[icon]]` table:
+///
+/// ```toml
+/// [[icon]]
+/// id = "arrow-right"
+/// path = "arrows/right.svg"
+/// aliases = ["next"]
+/// codepoint = "U+E001"
+/// unicode = ["U+2192"]
+/// tags = ["navigation"]
+/// advance-width = "proportional"
+/// padding = 0.05
+/// scale = 0.9
+/// offset = [0.02, 0]
+/// fit = "view-box"
+/// variants = { arrow-down = "rotate-90", arrow-left = "flip-horizontal" }
+/// directional = true
+/// components = ["arrow"]
+/// mark = "top-right"
+/// ```
+///
+/// Only one of `id` and `path` is required. The ID defaults to the path without file extension
+/// and the path defaults to the ID with the extension `.svg`. Paths are relative to the
+/// directory of the manifest. Codepoints are given either as `U+` followed by a hexadecimal
+/// number or as the character itself. The adjustments `padding`, `scale`, `offset` and `fit`
+/// replace the respective fields of the [`Adjustment`] that applies to the icon otherwise.
+/// Variants map IDs to the transforms `rotate-90`, `rotate-180`, `rotate-270`,
+/// `flip-horizontal` and `flip-vertical`, see [`Variant`]. Components are IDs of icons whose
+/// outlines the icon reuses, see [`Options::component_icons`]. An icon with a `mark` corner is
+/// attached to the preceding icon in that corner, see [`Options::mark_icons`].
+///
+/// Icons composed of a base icon and a badge are described by `[[compose]]` tables, see
+/// [`ComposedIcon`]:
+///
+/// ```toml
+/// [[compose]]
+/// id = "folder-with-lock"
+/// base = "folder"
+/// badge = "lock"
+/// corner = "bottom-right"
+/// scale = 0.5
+/// gap = 0.05
+/// ```
+///
+/// The corner is one of `top-left`, `top-right`, `bottom-left` and `bottom-right`, which is
+/// the default for composed icons. The scale defaults to `0.5` and the gap to `0.05`.
+///
+/// JSON manifests have the same structure with arrays of objects under the keys `icon` and
+/// `compose`:
+///
+/// ```json
+/// {
+///   "icon": [{ "id": "arrow-right", "variants": { "arrow-left": "flip-horizontal" } }],
+///   "compose": [{ "id": "folder-with-lock", "base": "folder", "badge": "lock" }]
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Manifest {
+    pub icons: Vec<ManifestIcon>,
+    pub composed_icons: Vec<ComposedIcon>,
+}
+
+/// Entry of a [`Manifest`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ManifestIcon {
+    pub id: String,
+    pub path: PathBuf,
+    /// Additional IDs under which the icon is available, each with its own codepoint
+    pub aliases: Vec<String>,
+    /// Fixed codepoint that is not assigned by the codepoint allocator
+    pub codepoint: Option<char>,
+    /// See [`Options::unicode_codepoints`]
+    pub unicode: Vec<char>,
+    /// Keywords for searching icons, which are not written to the font but listed in
+    /// [`Report::tags`](super::Report::tags)
+    pub tags: Vec<String>,
+    /// Takes precedence over [`Options::advance_width_overrides`]
+    pub advance_width: Option<AdvanceWidth>,
+    /// See [`Adjustment::padding`]
+    pub padding: Option<f64>,
+    /// See [`Adjustment::scale`]
+    pub scale: Option<f64>,
+    /// See [`Adjustment::offset`]
+    pub offset: Option<(f64, f64)>,
+    /// See [`Adjustment::fit`]
+    pub fit: Option<Fit>,
+    /// IDs and transforms of glyphs derived from the icon
+    pub variants: Vec<(String, VariantTransform)>,
+    /// See [`Options::directional_icons`]
+    pub directional: bool,
+    /// See [`Options::component_icons`]
+    pub components: Vec<String>,
+    /// See [`Options::mark_icons`]
+    pub mark: Option<Corner>,
+}
+
+impl Manifest {
+    /// Reads a manifest file and resolves the paths of icons relative to its directory.
+    ///
+    /// Files with the extension `.json` are read as JSON and all other files as TOML.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)?;
+        let mut manifest = match path.extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => Self::from_json(&source)?,
+            _ => Self::from_toml(&source)?,
+        };
+
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for icon in &mut manifest.icons {
+            icon.path = dir.join(&icon.path);
+        }
+
+        Ok(manifest)
+    }
+
+    /// Parses a TOML manifest, paths of icons are kept as they are.
+    pub fn from_toml(source: &str) -> Result<Self, Error> {
+        let document: DocumentMut = source
+            .parse()
+            .map_err(|error| Error::InvalidManifest(format!("{error}")))?;
+
+        Self::from_document(&document)
+    }
+
+    /// Parses a JSON manifest, paths of icons are kept as they are.
+    pub fn from_json(source: &str) -> Result<Self, Error> {
+        let value: serde_json::Value = serde_json::from_str(source)
+            .map_err(|error| Error::InvalidManifest(format!("{error}")))?;
+        // The JSON structure is converted to TOML tables, so that both formats are validated
+        // alike
+        let Item::Table(table) = json_item(value)? else {
+            return Err(Error::InvalidManifest("manifest must be an object".into()));
+        };
+
+        Self::from_document(&DocumentMut::from(table))
+    }
+
+    /// Returns the path of the manifest file in a directory, if there is one.
+    pub(crate) fn find(dir: &Path) -> Option<PathBuf> {
+        MANIFEST_FILE_NAMES
+            .iter()
+            .map(|file_name| dir.join(file_name))
+            .find(|path| path.is_file())
+    }
+
+    fn from_document(document: &DocumentMut) -> Result<Self, Error> {
+        let mut icons = vec![];
+        let mut composed_icons = vec![];
+        for (key, item) in document.iter() {
+            if key != "icon" && key != "compose" {
+                return Err(Error::InvalidManifest(format!("unknown key `{key}`")));
+            }
+            let tables = item.as_array_of_tables().ok_or_else(|| {
+                Error::InvalidManifest(format!("`{key}` must be an array of tables"))
+            })?;
+            for table in tables {
+                match key {
+                    "icon" => icons.push(parse_icon(table)?),
+                    _ => composed_icons.push(parse_composed_icon(table)?),
+                }
+            }
+        }
+
+        Ok(Self {
+            icons,
+            composed_icons,
+        })
+    }
+
+    /// Returns the IDs of icons that have tags together with their tags.
+    pub(crate) fn tags(&self) -> Vec<(String, Vec<String>)> {
+        self.icons
+            .iter()
+            .filter(|icon| !icon.tags.is_empty())
+            .map(|icon| (icon.id.clone(), icon.tags.clone()))
+            .collect()
+    }
+
+    /// Returns the IDs and paths of all icons and their aliases.
+    pub(crate) fn svg_files(&self) -> Vec<(String, PathBuf)> {
+        self.icons
+            .iter()
+            .flat_map(|icon| {
+                std::iter::once(&icon.id)
+                    .chain(&icon.aliases)
+                    .map(|icon_id| (icon_id.clone(), icon.path.clone()))
+            })
+            .collect()
+    }
+
+    /// Adds the advance widths, adjustments, variants, directional icons, components, marks and
+    /// standard Unicode codepoints of the icons as well as the composed icons to the options.
+    pub(crate) fn apply_overrides(&self, options: &mut Options) {
+        let mut overrides = vec![];
+        let mut adjustment_overrides = vec![];
+        let mut mark_icons = vec![];
+        for icon in &self.icons {
+            let icon_ids = || std::iter::once(&icon.id).chain(&icon.aliases);
+            if let Some(advance_width) = icon.advance_width {
+                overrides.extend(icon_ids().map(|icon_id| (icon_id.clone(), advance_width)));
+            }
+            if let Some(adjustment) = icon.adjustment(options.adjustment_for(&icon.id)) {
+                adjustment_overrides
+                    .extend(icon_ids().map(|icon_id| (icon_id.clone(), adjustment)));
+            }
+            if let Some(corner) = icon.mark {
+                mark_icons.extend(icon_ids().map(|icon_id| (icon_id.clone(), corner)));
+            }
+            if icon.directional {
+                options.directional_icons.extend(icon_ids().cloned());
+            }
+            options
+                .variants
+                .extend(icon.variants.iter().map(|(id, transform)| Variant {
+                    source_id: icon.id.clone(),
+                    id: id.clone(),
+                    transform: *transform,
+                }));
+            if !icon.unicode.is_empty() {
+                options
+                    .unicode_codepoints
+                    .entry(icon.id.clone())
+                    .or_default()
+                    .extend(&icon.unicode);
+            }
+            if !icon.components.is_empty() {
+                options
+                    .component_icons
+                    .entry(icon.id.clone())
+                    .or_default()
+                    .extend(icon.components.iter().cloned());
+            }
+        }
+        overrides.append(&mut options.advance_width_overrides);
+        options.advance_width_overrides = overrides;
+        adjustment_overrides.append(&mut options.adjustment_overrides);
+        options.adjustment_overrides = adjustment_overrides;
+        mark_icons.append(&mut options.mark_icons);
+        options.mark_icons = mark_icons;
+        options
+            .composed_icons
+            .extend(self.composed_icons.iter().cloned());
+    }
+
+    /// Returns the fixed codepoints of the icons.
+    pub(crate) fn codepoints(&self) -> IconMap {
+        self.icons
+            .iter()
+            .filter_map(|icon| Some((icon.id.clone(), icon.codepoint?)))
+            .collect()
+    }
+}
+
+impl ManifestIcon {
+    /// Returns the given adjustment with the fields that are set for the icon replaced, or
+    /// `None` if no field is set.
+    fn adjustment(&self, adjustment: Adjustment) -> Option<Adjustment> {
+        if self.padding.is_none()
+            && self.scale.is_none()
+            && self.offset.is_none()
+            && self.fit.is_none()
+        {
+            return None;
+        }
+
+        Some(Adjustment {
+            padding: self.padding.unwrap_or(adjustment.padding),
+            scale: self.scale.unwrap_or(adjustment.scale),
+            offset: self.offset.unwrap_or(adjustment.offset),
+            fit: self.fit.unwrap_or(adjustment.fit),
+        })
+    }
+}
+
+fn parse_icon(table: &Table) -> Result<ManifestIcon, Error> {
+    if let Some((key, _)) = table.iter().find(|(key, _)| !ICON_KEYS.contains(key)) {
+        return Err(Error::InvalidManifest(format!(
+            "unknown key `{key}` of icon"
+        )));
+    }
+
+    let (id, path) = match (string(table, "id")?, string(table, "path")?) {
+        (Some(id), Some(path)) => (id, PathBuf::from(path)),
+        (Some(id), None) => {
+            let path = PathBuf::from(format!("{id}.svg"));
+            (id, path)
+        }
+        (None, Some(path)) => {
+            let path = PathBuf::from(path);
+            let id = path.with_extension("").to_string_lossy().into_owned();
+            (id, path)
+        }
+        (None, None) => {
+            return Err(Error::InvalidManifest(
+                "icon requires an `id` or a `path`".into(),
+            ))
+        }
+    };
+
+    let codepoint = string(table, "codepoint")?
+        .map(|value| parse_codepoint(&value))
+        .transpose()?;
+    let unicode = strings(table, "unicode")?
+        .iter()
+        .map(|value| parse_codepoint(value))
+        .collect::<Result<_, _>>()?;
+    let advance_width = string(table, "advance-width")?
+        .map(|value| match value.as_str() {
+            "square" => Ok(AdvanceWidth::Square),
+            "proportional" => Ok(AdvanceWidth::Proportional),
+            "view-box" => Ok(AdvanceWidth::ViewBox),
+            _ => Err(Error::InvalidManifest(format!(
+                "unknown advance width `{value}` of {id}"
+            ))),
+        })
+        .transpose()?;
+    let offset = match numbers(table, "offset")?.as_deref() {
+        None => None,
+        Some(&[x, y]) => Some((x, y)),
+        Some(_) => {
+            return Err(Error::InvalidManifest(format!(
+                "`offset` of {id} must consist of two numbers"
+            )))
+        }
+    };
+    let fit = string(table, "fit")?
+        .map(|value| match value.as_str() {
+            "contain" => Ok(Fit::Contain),
+            "view-box" => Ok(Fit::ViewBox),
+            "cover" => Ok(Fit::Cover),
+            _ => Err(Error::InvalidManifest(format!(
+                "unknown fit `{value}` of {id}"
+            ))),
+        })
+        .transpose()?;
+
+    Ok(ManifestIcon {
+        aliases: strings(table, "aliases")?,
+        codepoint,
+        unicode,
+        tags: strings(table, "tags")?,
+        advance_width,
+        padding: number(table, "padding")?,
+        scale: positive_number(table, "scale", &id)?,
+        offset,
+        fit,
+        variants: variants(table, &id)?,
+        directional: boolean(table, "directional")?.unwrap_or(false),
+        components: strings(table, "components")?,
+        mark: string(table, "mark")?
+            .map(|value| parse_corner(&value, &id))
+            .transpose()?,
+        id,
+        path,
+    })
+}
+
+fn parse_composed_icon(table: &Table) -> Result<ComposedIcon, Error> {
+    if let Some((key, _)) = table.iter().find(|(key, _)| !COMPOSE_KEYS.contains(key)) {
+        return Err(Error::InvalidManifest(format!(
+            "unknown key `{key}` of composed icon"
+        )));
+    }
+
+    let required = |key: &str| {
+        string(table, key)?
+            .ok_or_else(|| Error::InvalidManifest(format!("composed icon requires a `{key}`")))
+    };
+    let id = required("id")?;
+    let corner = string(table, "corner")?
+        .map(|value| parse_corner(&value, &id))
+        .transpose()?
+        .unwrap_or_default();
+
+    Ok(ComposedIcon {
+        base_id: required("base")?,
+        badge_id: required("badge")?,
+        corner,
+        scale: positive_number(table, "scale", &id)?.unwrap_or(0.5),
+        gap: non_negative_number(table, "gap", &id)?.unwrap_or(0.05),
+        id,
+    })
+}
+
+/// Returns a number that must be positive and finite.
+fn positive_number(table: &Table, key: &str, id: &str) -> Result<Option<f64>, Error> {
+    let value = number(table, key)?;
+    match value {
+        Some(value) if !is_positive(value) => Err(Error::InvalidManifest(format!(
+            "`{key}` of {id} must be a positive number"
+        ))),
+        _ => Ok(value),
+    }
+}
+
+/// Returns a number that must be finite and must not be negative.
+fn non_negative_number(table: &Table, key: &str, id: &str) -> Result<Option<f64>, Error> {
+    let value = number(table, key)?;
+    match value {
+        Some(value) if !(value >= 0.0 && value.is_finite()) => Err(Error::InvalidManifest(
+            format!("`{key}` of {id} must not be negative"),
+        )),
+        _ => Ok(value),
+    }
+}
+
+fn string(table: &Table, key: &str) -> Result<Option<String>, Error> {
+    table
+        .get(key)
+        .map(|item| {
+            item.as_str()
+                .map(String::from)
+                .ok_or_else(|| Error::InvalidManifest(format!("`{key}` must be a string")))
+        })
+        .transpose()
+}
+
+fn boolean(table: &Table, key: &str) -> Result<Option<bool>, Error> {
+    table
+        .get(key)
+        .map(|item| {
+            item.as_bool()
+                .ok_or_else(|| Error::InvalidManifest(format!("`{key}` must be a boolean")))
+        })
+        .transpose()
+}
+
+fn strings(table: &Table, key: &str) -> Result<Vec<String>, Error> {
+    let Some(item) = table.get(key) else {
+        return Ok(vec![]);
+    };
+
+    let invalid = || Error::InvalidManifest(format!("`{key}` must be an array of strings"));
+    item.as_array()
+        .ok_or_else(invalid)?
+        .iter()
+        .map(|value| value.as_str().map(String::from).ok_or_else(invalid))
+        .collect()
+}
+
+fn variants(table: &Table, id: &str) -> Result<Vec<(String, VariantTransform)>, Error> {
+    let Some(item) = table.get("variants") else {
+        return Ok(vec![]);
+    };
+
+    let invalid = || Error::InvalidManifest("`variants` must be a table of strings".into());
+    item.as_table_like()
+        .ok_or_else(invalid)?
+        .iter()
+        .map(|(variant_id, item)| {
+            let transform = match item.as_str().ok_or_else(invalid)? {
+                "rotate-90" => VariantTransform::Rotate90,
+                "rotate-180" => VariantTransform::Rotate180,
+                "rotate-270" => VariantTransform::Rotate270,
+                "flip-horizontal" => VariantTransform::FlipHorizontal,
+                "flip-vertical" => VariantTransform::FlipVertical,
+                value => {
+                    return Err(Error::InvalidManifest(format!(
+                        "unknown transform `{value}` of {id}"
+                    )))
+                }
+            };
+            Ok((variant_id.to_string(), transform))
+        })
+        .collect()
+}
+
+fn number(table: &Table, key: &str) -> Result<Option<f64>, Error> {
+    table
+        .get(key)
+        .map(|item| {
+            item.as_float()
+                .or_else(|| item.as_integer().map(|value| value as f64))
+                .ok_or_else(|| Error::InvalidManifest(format!("`{key}` must be a number")))
+        })
+        .transpose()
+}
+
+fn numbers(table: &Table, key: &str) -> Result<Option<Vec<f64>>, Error> {
+    let Some(item) = table.get(key) else {
+        return Ok(None);
+    };
+
+    let invalid = || Error::InvalidManifest(format!("`{key}` must be an array of numbers"));
+    item.as_array()
+        .ok_or_else(invalid)?
+        .iter()
+        .map(|value| {
+            value
+                .as_float()
+                .or_else(|| value.as_integer().map(|value| value as f64))
+                .ok_or_else(invalid)
+        })
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+/// Converts a JSON value to a TOML item, objects become tables and arrays of objects become
+/// arrays of tables.
+fn json_item(value: serde_json::Value) -> Result<Item, Error> {
+    match value {
+        serde_json::Value::Object(object) => {
+            let mut table = Table::new();
+            for (key, value) in object {
+                table.insert(&key, json_item(value)?);
+            }
+            Ok(Item::Table(table))
+        }
+        serde_json::Value::Array(values)
+            if !values.is_empty() && values.iter().all(serde_json::Value::is_object) =>
+        {
+            let mut tables = ArrayOfTables::new();
+            for value in values {
+                if let Item::Table(table) = json_item(value)? {
+                    tables.push(table);
+                }
+            }
+            Ok(Item::ArrayOfTables(tables))
+        }
+        value => json_value(value).map(Item::Value),
+    }
+}
+
+/// Converts a JSON value to a TOML value, objects become inline tables.
+fn json_value(value: serde_json::Value) -> Result<Value, Error> {
+    match value {
+        serde_json::Value::Null => Err(Error::InvalidManifest("`null` is not supported".into())),
+        serde_json::Value::Bool(value) => Ok(value.into()),
+        serde_json::Value::Number(number) => match number.as_i64() {
+            Some(value) => Ok(value.into()),
+            None => Ok(number.as_f64().unwrap_or(f64::NAN).into()),
+        },
+        serde_json::Value::String(value) => Ok(value.into()),
+        serde_json::Value::Array(values) => values
+            .into_iter()
+            .map(json_value)
+            .collect::<Result<Array, _>>()
+            .map(Value::Array),
+        serde_json::Value::Object(object) => object
+            .into_iter()
+            .map(|(key, value)| Ok((key, json_value(value)?)))
+            .collect::<Result<InlineTable, _>>()
+            .map(Value::InlineTable),
+    }
+}
+
+fn parse_corner(value: &str, id: &str) -> Result<Corner, Error> {
+    match value {
+        "top-left" => Ok(Corner::TopLeft),
+        "top-right" => Ok(Corner::TopRight),
+        "bottom-left" => Ok(Corner::BottomLeft),
+        "bottom-right" => Ok(Corner::BottomRight),
+        _ => Err(Error::InvalidManifest(format!(
+            "unknown corner `{value}` of {id}"
+        ))),
+    }
+}
+
+/// Parses a codepoint like `U+E001` or a single character.
+fn parse_codepoint(value: &str) -> Result<char, Error> {
+    let codepoint = match value.strip_prefix("U+") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+        None => {
+            let mut chars = value.chars();
+            chars.next().filter(|_| chars.next().is_none())
+        }
+    };
+
+    codepoint.ok_or_else(|| Error::InvalidManifest(format!("invalid codepoint `{value}`")))
+}
+
+/// Returns the SVG files of the manifest followed by scanned files that it does not describe.
+pub(crate) fn merge_svg_files(
+    manifest: &Manifest,
+    scanned_files: Vec<(String, PathBuf)>,
+) -> Vec<(String, PathBuf)> {
+    let mut files = manifest.svg_files();
+
+    let canonical = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let described: HashMap<PathBuf, &str> = files
+        .iter()
+        .map(|(icon_id, path)| (canonical(path), icon_id.as_str()))
+        .collect();
+    let mut remaining: Vec<(String, PathBuf)> = scanned_files
+        .into_iter()
+        .filter(|(icon_id, path)| {
+            !described.contains_key(&canonical(path))
+                && !described
+                    .values()
+                    .any(|described_id| described_id == icon_id)
+        })
+        .collect();
+    files.append(&mut remaining);
+
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_manifest() {
+        let source = r#"
+            [[icon]]
+            id = "arrow-right"
+            path = "arrows/right.svg"
+            aliases = ["next"]
+            codepoint = "U+E010"
+            unicode = ["→"]
+            tags = ["navigation"]
+            advance-width = "proportional"
+            scale = 0.9
+            offset = [0, -0.05]
+            variants = { arrow-left = "flip-horizontal" }
+            directional = true
+            components = ["arrow"]
+
+            [[icon]]
+            path = "jam/box.svg"
+            mark = "bottom-left"
+
+            [[compose]]
+            id = "box-with-lock"
+            base = "jam/box"
+            badge = "lock"
+            corner = "top-left"
+        "#;
+
+        let manifest = Manifest::from_toml(source).unwrap();
+
+        assert_eq!(
+            manifest.icons[0],
+            ManifestIcon {
+                id: "arrow-right".to_string(),
+                path: PathBuf::from("arrows/right.svg"),
+                aliases: vec!["next".to_string()],
+                codepoint: Some('\u{E010}'),
+                unicode: vec!['\u{2192}'],
+                tags: vec!["navigation".to_string()],
+                advance_width: Some(AdvanceWidth::Proportional),
+                padding: None,
+                scale: Some(0.9),
+                offset: Some((0.0, -0.05)),
+                fit: None,
+                variants: vec![("arrow-left".to_string(), VariantTransform::FlipHorizontal)],
+                directional: true,
+                components: vec!["arrow".to_string()],
+                mark: None,
+            }
+        );
+        assert_eq!(manifest.icons[1].id, "jam/box");
+        assert_eq!(manifest.icons[1].mark, Some(Corner::BottomLeft));
+        assert_eq!(
+            manifest.composed_icons,
+            [ComposedIcon {
+                id: "box-with-lock".to_string(),
+                base_id: "jam/box".to_string(),
+                badge_id: "lock".to_string(),
+                corner: Corner::TopLeft,
+                scale: 0.5,
+                gap: 0.05,
+            }]
+        );
+        assert_eq!(
+            manifest.tags(),
+            [("arrow-right".to_string(), vec!["navigation".to_string()])]
+        );
+        assert_eq!(
+            manifest.svg_files(),
+            [
+                ("arrow-right".to_string(), PathBuf::from("arrows/right.svg")),
+                ("next".to_string(), PathBuf::from("arrows/right.svg")),
+                ("jam/box".to_string(), PathBuf::from("jam/box.svg")),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_json_manifest() {
+        let source = r#"{
+            "icon": [
+                {
+                    "id": "arrow-right",
+                    "codepoint": "U+E010",
+                    "offset": [0, -0.05],
+                    "variants": { "arrow-left": "flip-horizontal" },
+                    "directional": true
+                },
+                { "path": "jam/box.svg", "mark": "bottom-left" }
+            ],
+            "compose": [{ "id": "box-with-lock", "base": "jam/box", "badge": "lock" }]
+        }"#;
+
+        let manifest = Manifest::from_json(source).unwrap();
+
+        let icon = &manifest.icons[0];
+        assert_eq!(icon.codepoint, Some('\u{E010}'));
+        assert_eq!(icon.offset, Some((0.0, -0.05)));
+        assert_eq!(
+            icon.variants,
+            [("arrow-left".to_string(), VariantTransform::FlipHorizontal)]
+        );
+        assert!(icon.directional);
+        assert_eq!(manifest.icons[1].id, "jam/box");
+        assert_eq!(manifest.icons[1].mark, Some(Corner::BottomLeft));
+        assert_eq!(manifest.composed_icons[0].corner, Corner::BottomRight);
+
+        for source in [
+            "[]",
+            r#"{ "icon": { "id": "a" } }"#,
+            r#"{ "icon": [{ "id": "a", "scale": null }] }"#,
+            r#"{ "icon": [{ "id": "a", "size": 2 }] }"#,
+        ] {
+            assert!(matches!(
+                Manifest::from_json(source),
+                Err(Error::InvalidManifest(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn invalid_manifest() {
+        for source in [
+            "[[icon]]\nid = \"a\"\nsize = 2",
+            "[[icon]]\ncodepoint = \"U+E000\"",
+            "[[icon]]\nid = \"a\"\ncodepoint = \"ab\"",
+            "[[icon]]\nid = \"a\"\nadvance-width = \"wide\"",
+            "[[icon]]\nid = \"a\"\noffset = [1]",
+            "[[icon]]\nid = \"a\"\nscale = 0",
+            "[[icon]]\nid = \"a\"\nscale = -0.5",
+            "[[icon]]\nid = \"a\"\nscale = nan",
+            "icon = \"a\"",
+            "[[compose]]\nid = \"a\"\nbase = \"b\"",
+            "[[compose]]\nid = \"a\"\nbase = \"b\"\nbadge = \"c\"\nscale = 0",
+            "[[compose]]\nid = \"a\"\nbase = \"b\"\nbadge = \"c\"\ngap = -0.1",
+            "[[compose]]\nid = \"a\"\nbase = \"b\"\nbadge = \"c\"\ncorner = \"left\"",
+        ] {
+            assert!(matches!(
+                Manifest::from_toml(source),
+                Err(Error::InvalidManifest(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use read_fonts::{tables::os2::SelectionFlags, FontRef, TableProvider};
+use std::{fs, path::Path};
+
+use crate::Error;
+
+// Metrics of typical UI fonts in relation to the em height
+const TYPICAL_ASCENDER: f64 = 0.95;
+const TYPICAL_DESCENDER: f64 = -0.25;
+const TYPICAL_X_HEIGHT: f64 = 0.52;
+const TYPICAL_CAP_HEIGHT: f64 = 0.71;
+
+/// Vertical metrics of the font in relation to the em height.
+///
+/// Values are multiplied with the units per em of the font, so that they do not need to be
+/// adjusted when the units per em change. Glyphs are scaled to the icon height and centered
+/// vertically in the area that spans from the baseline offset to the baseline offset plus the
+/// icon height.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VerticalMetrics {
+    /// Distance from the baseline to the top of the line.
+    pub ascender: f64,
+    /// Distance from the baseline to the bottom of the line, negative values are below the baseline.
+    pub descender: f64,
+    /// Additional space between lines.
+    pub line_gap: f64,
+    /// Vertical offset of the icon area from the baseline, negative values move glyphs down.
+    pub baseline_offset: f64,
+    /// Height of the icon area.
+    pub icon_height: f64,
+    /// Height of lowercase letters of the accompanying text, only written to the font.
+    pub x_height: f64,
+}
+
+impl VerticalMetrics {
+    /// Metrics for icons placed next to text of typical UI fonts.
+    ///
+    /// Icons are centered between the x-height and the cap height and the line height matches
+    /// fonts like Roboto or Inter, so that icons and text share the same baseline.
+    pub fn text_aligned() -> Self {
+        let center = (TYPICAL_X_HEIGHT + TYPICAL_CAP_HEIGHT) / 4.0;
+
+        Self {
+            ascender: TYPICAL_ASCENDER,
+            descender: TYPICAL_DESCENDER,
+            line_gap: 0.0,
+            baseline_offset: center - 0.5,
+            icon_height: 1.0,
+            x_height: TYPICAL_X_HEIGHT,
+        }
+    }
+
+    /// Reads the metrics of a reference font file like [`VerticalMetrics::from_font_bytes`].
+    pub fn from_font_file(font_path: impl AsRef<Path>) -> Result<Self, Error> {
+        let font_data = fs::read(font_path)?;
+
+        Self::from_font_bytes(font_data)
+    }
+
+    /// Reads the metrics of a reference font, so that icons share the line height of its text.
+    ///
+    /// The ascender, descender and line gap are taken from the `OS/2` table if the font prefers
+    /// its typographic metrics and from the `hhea` table otherwise. Icons sit on the baseline
+    /// and are scaled to the cap height of the reference font, whose x-height is copied.
+    ///
+    /// Use [`Options::match_reference_font`](super::Options::match_reference_font) to copy the
+    /// units per em as well.
+    pub fn from_font_bytes(font_data: impl AsRef<[u8]>) -> Result<Self, Error> {
+        let font = FontRef::new(font_data.as_ref())?;
+        let units_per_em = font.head()?.units_per_em() as f64;
+        let hhea = font.hhea()?;
+        let os2 = font.os2().ok();
+
+        let (ascender, descender, line_gap) = match os2 {
+            Some(ref os2)
+                if os2
+                    .fs_selection()
+                    .contains(SelectionFlags::USE_TYPO_METRICS) =>
+            {
+                (
+                    os2.s_typo_ascender(),
+                    os2.s_typo_descender(),
+                    os2.s_typo_line_gap(),
+                )
+            }
+            _ => (
+                hhea.ascender().to_i16(),
+                hhea.descender().to_i16(),
+                hhea.line_gap().to_i16(),
+            ),
+        };
+        let cap_height = os2
+            .as_ref()
+            .and_then(|os2| os2.s_cap_height())
+            .filter(|&cap_height| cap_height > 0)
+            .map_or(TYPICAL_CAP_HEIGHT, |cap_height| {
+                cap_height as f64 / units_per_em
+            });
+        // Older fonts lack the x-height, it is estimated from the cap height then
+        let x_height = os2
+            .and_then(|os2| os2.sx_height())
+            .filter(|&x_height| x_height > 0)
+            .map_or(
+                cap_height * TYPICAL_X_HEIGHT / TYPICAL_CAP_HEIGHT,
+                |x_height| x_height as f64 / units_per_em,
+            );
+
+        Ok(Self {
+            ascender: ascender as f64 / units_per_em,
+            descender: descender as f64 / units_per_em,
+            line_gap: line_gap as f64 / units_per_em,
+            baseline_offset: 0.0,
+            icon_height: cap_height,
+            x_height,
+        })
+    }
+}
+
+impl Default for VerticalMetrics {
+    /// Glyphs sit on the baseline and the line height equals the em height.
+    fn default() -> Self {
+        Self {
+            ascender: 1.0,
+            descender: 0.0,
+            line_gap: 0.0,
+            baseline_offset: 0.0,
+            icon_height: 1.0,
+            x_height: TYPICAL_X_HEIGHT,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use write_fonts::{
+        tables::{head::Head, hhea::Hhea, os2::Os2},
+        FontBuilder,
+    };
+
+    fn reference_font(use_typo_metrics: bool) -> Vec<u8> {
+        let mut font = FontBuilder::new();
+        font.add_table(&Head {
+            units_per_em: 2048,
+            ..Head::default()
+        })
+        .unwrap();
+        font.add_table(&Hhea {
+            ascender: 1900.into(),
+            descender: (-500).into(),
+            line_gap: 0.into(),
+            ..Hhea::default()
+        })
+        .unwrap();
+        let fs_selection = if use_typo_metrics {
+            SelectionFlags::USE_TYPO_METRICS
+        } else {
+            SelectionFlags::empty()
+        };
+        font.add_table(&Os2 {
+            s_typo_ascender: 1536,
+            s_typo_descender: -512,
+            s_typo_line_gap: 410,
+            fs_selection,
+            ul_code_page_range_1: Some(0),
+            ul_code_page_range_2: Some(0),
+            sx_height: Some(1082),
+            s_cap_height: Some(1434),
+            us_default_char: Some(0),
+            us_break_char: Some(32),
+            us_max_context: Some(0),
+            ..Os2::default()
+        })
+        .unwrap();
+
+        font.build()
+    }
+
+    #[test]
+    fn metrics_from_hhea() {
+        let metrics = VerticalMetrics::from_font_bytes(reference_font(false)).unwrap();
+
+        assert_eq!(
+            metrics,
+            VerticalMetrics {
+                ascender: 1900.0 / 2048.0,
+                descender: -500.0 / 2048.0,
+                line_gap: 0.0,
+                baseline_offset: 0.0,
+                icon_height: 1434.0 / 2048.0,
+                x_height: 1082.0 / 2048.0,
+            }
+        );
+    }
+
+    #[test]
+    fn metrics_from_typo_metrics() {
+        let metrics = VerticalMetrics::from_font_bytes(reference_font(true)).unwrap();
+
+        assert_eq!(
+            (metrics.ascender, metrics.descender, metrics.line_gap),
+            (0.75, -0.25, 410.0 / 2048.0)
+        );
+    }
+}
@@ -0,0 +1,359 @@
+use read_fonts::{FontRef, TableProvider};
+use std::{collections::HashMap, ops::RangeInclusive, path::PathBuf, sync::Arc};
+
+use super::{
+    Adjustment, CodepointAllocator, ColorFormat, ComposedIcon, Corner, SequentialAllocator,
+    Variant, VerticalMetrics,
+};
+use crate::Error;
+
+/// Options that control how SVG icons are converted into a font.
+///
+/// Use [`Options::default`] as a starting point and adjust individual fields as needed.
+#[derive(Clone, Debug)]
+pub struct Options {
+    /// Maximum deviation in font units when cubic curves are approximated by quadratic curves.
+    ///
+    /// Default: `0.5`
+    pub curve_tolerance: f64,
+
+    /// Maximum deviation in font units when strokes are converted to outlines.
+    ///
+    /// Default: `0.5`
+    pub stroke_tolerance: f64,
+
+    /// Tolerance in font units for simplifying outlines before glyphs are created.
+    ///
+    /// Removes zero-length segments, merges collinear lines and refits runs of curves.
+    /// Simplification is disabled if set to `None`.
+    ///
+    /// Default: `None`
+    pub simplify_tolerance: Option<f64>,
+
+    /// Merges overlapping contours of a glyph into non-overlapping contours.
+    ///
+    /// Overlapping contours can cause rendering artifacts with some rasterizers.
+    /// The curve tolerance applies when curves are refitted after merging.
+    ///
+    /// Default: `false`
+    pub remove_overlaps: bool,
+
+    /// Patterns for IDs of SVG elements that are not converted into outlines.
+    ///
+    /// Matching elements are removed together with their children, which makes it possible to
+    /// exclude guide layers and bounding boxes exported from design tools. A pattern is matched
+    /// against the whole ID and may contain `*` as a wildcard for any sequence of characters.
+    pub exclude_ids: Vec<String>,
+
+    /// Patterns for classes of SVG elements that are not converted into outlines.
+    ///
+    /// An element is excluded if any of its classes matches a pattern.
+    /// Patterns follow the same rules as [`Options::exclude_ids`].
+    pub exclude_classes: Vec<String>,
+
+    /// How the advance widths of glyphs are determined.
+    ///
+    /// Default: [`AdvanceWidth::Square`]
+    pub advance_width: AdvanceWidth,
+
+    /// Advance width modes for individual icons that take precedence over
+    /// [`Options::advance_width`].
+    ///
+    /// Each entry consists of a pattern that is matched against the icon ID and the mode for
+    /// matching icons. Patterns follow the same rules as [`Options::exclude_ids`], so `"logos/*"`
+    /// matches all icons in the `logos` subdirectory. The first matching entry applies.
+    pub advance_width_overrides: Vec<(String, AdvanceWidth)>,
+
+    /// Adjustments of the size and position of icons within their glyphs.
+    ///
+    /// Default: [`Adjustment::default`]
+    pub adjustment: Adjustment,
+
+    /// Adjustments for individual icons that take precedence over [`Options::adjustment`].
+    ///
+    /// Patterns are matched against icon IDs like for [`Options::advance_width_overrides`].
+    pub adjustment_overrides: Vec<(String, Adjustment)>,
+
+    /// Glyphs that are derived from icons by rotation or mirroring.
+    ///
+    /// Each variant is converted like an icon with its own ID, so overrides for the ID apply.
+    pub variants: Vec<Variant>,
+
+    /// Icons that are composed of a base icon and a badge.
+    ///
+    /// Composed icons are converted like icons with their own IDs, so overrides for the ID
+    /// apply. The parts remain available as icons of their own and must exist.
+    pub composed_icons: Vec<ComposedIcon>,
+
+    /// Patterns for IDs of directional icons, like arrows, that are mirrored in right-to-left text.
+    ///
+    /// A mirrored glyph without codepoint is added for each matching icon together with an
+    /// OpenType `rtlm` substitution, which text shaping applies in right-to-left runs.
+    /// Patterns follow the same rules as [`Options::exclude_ids`].
+    pub directional_icons: Vec<String>,
+
+    /// Patterns for IDs of icons, like badges, that are attached to the preceding icon at
+    /// render time, each with the corner of the preceding icon they are placed in.
+    ///
+    /// Matching icons are classified as marks and an OpenType `mark` feature positions them,
+    /// so that the codepoint of an icon followed by the codepoint of a mark shows the mark in
+    /// the corner of the icon. Anchors are placed at the corners of the glyph bounds, so the
+    /// mark is typically reduced with [`Adjustment::scale`]. Marks have no advance width and
+    /// mirrored marks of [`Options::directional_icons`] attach to the mirrored corner. Patterns
+    /// follow the same rules as [`Options::exclude_ids`], the first matching entry applies.
+    pub mark_icons: Vec<(String, Corner)>,
+
+    /// Reuses the outlines of icons that are contained in other icons, like a file icon in a
+    /// file icon with a plus sign.
+    ///
+    /// Icons that contain all contours of another icon reference its glyph as a component of a
+    /// TrueType composite glyph instead of duplicating the outlines, which reduces the size of
+    /// large icon sets. Outlines only match if both icons are scaled alike, e.g. with
+    /// [`Fit::ViewBox`](super::Fit::ViewBox) and view boxes of the same size.
+    ///
+    /// Default: `false`
+    pub composite_glyphs: bool,
+
+    /// IDs of icons whose outlines are reused by an icon, keyed by the ID of the icon.
+    ///
+    /// Declared components are reused like with [`Options::composite_glyphs`], but only for
+    /// the listed pairs of icons.
+    pub component_icons: HashMap<String, Vec<String>>,
+
+    /// Adds color glyphs in the given format for icons with colors other than `currentColor`.
+    ///
+    /// Consecutive paths with the same paint form a layer, the fill or stroke colors and the
+    /// opacity of the SVG are kept and `currentColor` follows the color of the text. The glyph
+    /// outlines remain as a monochrome fallback for renderers without color font support.
+    /// Icons that only use `currentColor` or black, the default fill of SVG, are not affected.
+    ///
+    /// Default: `None`
+    pub color_format: Option<ColorFormat>,
+
+    /// Range of codepoints that are assigned to icons in order.
+    ///
+    /// Only codepoints of private use areas within the range are assigned. Use
+    /// `'\u{F0000}'..='\u{10FFFD}'` for the supplementary private use planes 15 and 16, which
+    /// offer far more codepoints than the 6400 of the Basic Multilingual Plane.
+    ///
+    /// Default: `'\u{E000}'..='\u{F8FF}'`
+    pub codepoint_range: RangeInclusive<char>,
+
+    /// Strategy for assigning codepoints of the range to icons.
+    ///
+    /// Default: [`SequentialAllocator`]
+    pub codepoint_allocator: Arc<dyn CodepointAllocator>,
+
+    /// Standard Unicode codepoints that are mapped to icons in addition to their private use
+    /// codepoints, e.g. U+2192 for an arrow.
+    ///
+    /// Text with these characters is still meaningful when copied or rendered with a fallback
+    /// font. Keys are icon IDs.
+    pub unicode_codepoints: HashMap<String, Vec<char>>,
+
+    /// Previously published font whose codepoints are kept.
+    ///
+    /// Icons that exist in the baseline font keep their codepoints and new icons are assigned
+    /// by the codepoint allocator to codepoints that are still free. Codepoints of removed
+    /// icons are not reused, the removed icons are listed in the [`Report`](super::Report).
+    ///
+    /// Default: `None`
+    pub baseline_font: Option<PathBuf>,
+
+    /// TOML or JSON file that describes icons, see [`Manifest`](super::Manifest).
+    ///
+    /// Icons in the source directory that the manifest does not describe are added as well.
+    /// Unlike [`make_font`](super::make_font), a manifest in the source directory is only read
+    /// if it is given here.
+    ///
+    /// Default: `None`
+    pub manifest: Option<PathBuf>,
+
+    /// Number of font units per em, which determines the precision of glyph coordinates.
+    ///
+    /// All metrics are derived from it. Must be between 16 and 16384.
+    ///
+    /// Default: `1000`
+    pub units_per_em: u16,
+
+    /// Minimum space to the left and to the right of each glyph as a fraction of the em height.
+    ///
+    /// Default: `0.0`
+    pub side_bearing: f64,
+
+    /// Vertical metrics of the font and vertical placement of glyphs.
+    ///
+    /// Default: [`VerticalMetrics::default`]
+    pub vertical_metrics: VerticalMetrics,
+}
+
+/// Determines the advance width of a glyph.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AdvanceWidth {
+    /// All glyphs have an advance width of one em.
+    ///
+    /// Icons are scaled to fit into the icon area and centered.
+    #[default]
+    Square,
+    /// The advance width follows the aspect ratio of the icon's outlines.
+    ///
+    /// Icons are scaled to the icon height.
+    Proportional,
+    /// The advance width follows the aspect ratio of the SVG view box.
+    ///
+    /// The view box is scaled to the icon height, which preserves the space
+    /// around the icon as drawn.
+    ViewBox,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            curve_tolerance: 0.5,
+            stroke_tolerance: 0.5,
+            simplify_tolerance: None,
+            remove_overlaps: false,
+            exclude_ids: vec![],
+            exclude_classes: vec![],
+            advance_width: AdvanceWidth::default(),
+            advance_width_overrides: vec![],
+            adjustment: Adjustment::default(),
+            adjustment_overrides: vec![],
+            variants: vec![],
+            composed_icons: vec![],
+            directional_icons: vec![],
+            mark_icons: vec![],
+            composite_glyphs: false,
+            component_icons: HashMap::new(),
+            color_format: None,
+            codepoint_range: '\u{E000}'..='\u{F8FF}',
+            codepoint_allocator: Arc::new(SequentialAllocator),
+            unicode_codepoints: HashMap::new(),
+            baseline_font: None,
+            manifest: None,
+            units_per_em: 1000,
+            side_bearing: 0.0,
+            vertical_metrics: VerticalMetrics::default(),
+        }
+    }
+}
+
+impl Options {
+    /// Copies the units per em and the vertical metrics of a reference font.
+    ///
+    /// See [`VerticalMetrics::from_font_bytes`] for the metrics that are used.
+    pub fn match_reference_font(&mut self, font_data: impl AsRef<[u8]>) -> Result<(), Error> {
+        let font = FontRef::new(font_data.as_ref())?;
+        self.units_per_em = font.head()?.units_per_em();
+        self.vertical_metrics = VerticalMetrics::from_font_bytes(font_data)?;
+
+        Ok(())
+    }
+
+    /// Returns `true` if an element with the given ID and classes should be excluded.
+    pub(crate) fn excludes_element(&self, id: Option<&str>, classes: Option<&str>) -> bool {
+        let excluded_id = id.is_some_and(|id| {
+            self.exclude_ids
+                .iter()
+                .any(|pattern| matches_pattern(pattern, id))
+        });
+        let excluded_class = classes.is_some_and(|classes| {
+            classes.split_whitespace().any(|class| {
+                self.exclude_classes
+                    .iter()
+                    .any(|pattern| matches_pattern(pattern, class))
+            })
+        });
+
+        excluded_id || excluded_class
+    }
+
+    /// Returns the advance width mode for the icon with the given ID.
+    pub(crate) fn advance_width_for(&self, icon_id: &str) -> AdvanceWidth {
+        self.advance_width_overrides
+            .iter()
+            .find(|(pattern, _)| matches_pattern(pattern, icon_id))
+            .map_or(self.advance_width, |&(_, advance_width)| advance_width)
+    }
+
+    /// Returns `true` if the icon with the given ID is mirrored in right-to-left text.
+    pub(crate) fn is_directional(&self, icon_id: &str) -> bool {
+        self.directional_icons
+            .iter()
+            .any(|pattern| matches_pattern(pattern, icon_id))
+    }
+
+    /// Returns the corner a mark is attached to if the icon with the given ID is a mark.
+    pub(crate) fn mark_corner(&self, icon_id: &str) -> Option<Corner> {
+        self.mark_icons
+            .iter()
+            .find(|(pattern, _)| matches_pattern(pattern, icon_id))
+            .map(|&(_, corner)| corner)
+    }
+
+    /// Returns `true` if the icon with the given ID may reuse the outlines of the other icon.
+    pub(crate) fn reuses_outlines(&self, icon_id: &str, component_id: &str) -> bool {
+        self.composite_glyphs
+            || self
+                .component_icons
+                .get(icon_id)
+                .is_some_and(|ids| ids.iter().any(|id| id == component_id))
+    }
+
+    /// Returns the adjustment for the icon with the given ID.
+    pub(crate) fn adjustment_for(&self, icon_id: &str) -> Adjustment {
+        self.adjustment_overrides
+            .iter()
+            .find(|(pattern, _)| matches_pattern(pattern, icon_id))
+            .map_or(self.adjustment, |&(_, adjustment)| adjustment)
+    }
+}
+
+/// Matches a value against a pattern where `*` stands for any sequence of characters.
+pub(crate) fn matches_pattern(pattern: &str, value: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+
+    let Some(mut rest) = value.strip_prefix(first) else {
+        return false;
+    };
+
+    let mut parts = parts.peekable();
+    if parts.peek().is_none() {
+        // Pattern without wildcard
+        return rest.is_empty();
+    }
+
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            // The last part has to match the end of the value
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("frame", "frame", true)]
+    #[case("frame", "frame-1", false)]
+    #[case("frame*", "frame-1", true)]
+    #[case("*guide*", "layer-guides", true)]
+    #[case("*-bbox", "icon-bbox", true)]
+    #[case("*-bbox", "icon-bbox-2", false)]
+    #[case("a*b*c", "abc", true)]
+    #[case("a*b*c", "acb", false)]
+    #[case("*", "anything", true)]
+    fn pattern_matching(#[case] pattern: &str, #[case] value: &str, #[case] expected: bool) {
+        assert_eq!(matches_pattern(pattern, value), expected);
+    }
+}
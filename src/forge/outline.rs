@@ -0,0 +1,413 @@
+use kurbo::{
+    simplify::{simplify_bezpath, SimplifyOptions},
+    BezPath, CubicBez, Line, ParamCurve, ParamCurveNearest, PathEl, PathSeg, Point, QuadBez, Shape,
+};
+
+mod knockout;
+mod overlap;
+
+pub use self::{knockout::knock_out, overlap::remove_overlaps};
+
+/// Simplifies Bézier paths so that they deviate at most by the given tolerance in path units.
+///
+/// Zero-length segments are removed, consecutive collinear lines are merged and runs of smoothly
+/// connected curves are refitted with fewer curves. Subpaths that are smaller than the tolerance
+/// are removed entirely.
+pub fn simplify_outline(bez_paths: Vec<BezPath>, tolerance: f64) -> Vec<BezPath> {
+    bez_paths
+        .iter()
+        .map(|bez_path| {
+            let mut result = BezPath::new();
+            for subpath in Subpath::split(bez_path) {
+                if let Some(subpath) = subpath.simplify(tolerance) {
+                    subpath.append_to(&mut result);
+                }
+            }
+            result
+        })
+        .filter(|bez_path| !bez_path.elements().is_empty())
+        .collect()
+}
+
+/// A sequence of connected segments
+struct Subpath {
+    segments: Vec<PathSeg>,
+    closed: bool,
+}
+
+impl Subpath {
+    /// Splits a path into subpaths, closing segments are added explicitly.
+    fn split(bez_path: &BezPath) -> Vec<Subpath> {
+        let mut subpaths = vec![];
+        let mut segments = vec![];
+        let mut start_point = Point::ZERO;
+        let mut current_point = Point::ZERO;
+
+        for element in bez_path.elements() {
+            match *element {
+                PathEl::MoveTo(point) => {
+                    if !segments.is_empty() {
+                        subpaths.push(Subpath {
+                            segments: std::mem::take(&mut segments),
+                            closed: false,
+                        });
+                    }
+                    start_point = point;
+                    current_point = point;
+                }
+                PathEl::LineTo(point) => {
+                    segments.push(PathSeg::Line(Line::new(current_point, point)));
+                    current_point = point;
+                }
+                PathEl::QuadTo(p1, p2) => {
+                    segments.push(PathSeg::Quad(QuadBez::new(current_point, p1, p2)));
+                    current_point = p2;
+                }
+                PathEl::CurveTo(p1, p2, p3) => {
+                    segments.push(PathSeg::Cubic(CubicBez::new(current_point, p1, p2, p3)));
+                    current_point = p3;
+                }
+                PathEl::ClosePath => {
+                    if current_point != start_point {
+                        segments.push(PathSeg::Line(Line::new(current_point, start_point)));
+                    }
+                    if !segments.is_empty() {
+                        subpaths.push(Subpath {
+                            segments: std::mem::take(&mut segments),
+                            closed: true,
+                        });
+                    }
+                    current_point = start_point;
+                }
+            }
+        }
+
+        if !segments.is_empty() {
+            subpaths.push(Subpath {
+                segments,
+                closed: false,
+            });
+        }
+
+        subpaths
+    }
+
+    /// Returns the simplified subpath or `None` if nothing visible remains.
+    fn simplify(mut self, tolerance: f64) -> Option<Subpath> {
+        let size = self
+            .segments
+            .iter()
+            .map(|segment| segment.bounding_box())
+            .reduce(|bbox1, bbox2| bbox1.union(bbox2))?
+            .size();
+        if size.max_side() <= tolerance {
+            return None;
+        }
+
+        self.remove_short_segments(tolerance);
+        if self.closed {
+            self.rotate_to_corner(tolerance);
+        }
+        self.merge_collinear_lines(tolerance);
+        self.refit_curves(tolerance);
+
+        Some(self)
+    }
+
+    /// Removes segments that stay within the tolerance of the end of the last kept segment and
+    /// reconnects their neighbours.
+    ///
+    /// Runs of short segments are only removed as long as they stay close to the last kept
+    /// point, so that the next kept segment is moved by at most the tolerance.
+    fn remove_short_segments(&mut self, tolerance: f64) {
+        let start_point = self.segments[0].start();
+        let mut segments: Vec<PathSeg> = Vec::with_capacity(self.segments.len());
+
+        for segment in self.segments.drain(..) {
+            let previous_end = segments
+                .last()
+                .map_or(start_point, |previous| previous.end());
+            if stays_within(segment, previous_end, tolerance) {
+                continue;
+            }
+            segments.push(with_start(segment, previous_end));
+        }
+
+        if self.closed {
+            if let Some(last) = segments.last_mut() {
+                // Removed segments before the start point stayed within the tolerance of it
+                if last.end() != start_point {
+                    *last = with_end(*last, start_point);
+                }
+            }
+            if let Some(first) = segments.first_mut() {
+                *first = with_start(*first, start_point);
+            }
+        }
+
+        self.segments = segments;
+    }
+
+    /// Rotates the segments of a closed subpath so that it starts at a corner, which allows
+    /// merging lines across the start point.
+    fn rotate_to_corner(&mut self, tolerance: f64) {
+        let len = self.segments.len();
+        let corner = (0..len).find(|&i| {
+            let previous = self.segments[(i + len - 1) % len];
+            let next = self.segments[i];
+            match (previous, next) {
+                (PathSeg::Line(line1), PathSeg::Line(line2)) => {
+                    !is_collinear(line1.p0, &[line1.p1], line2.p1, tolerance)
+                }
+                _ => true,
+            }
+        });
+
+        if let Some(corner) = corner {
+            self.segments.rotate_left(corner);
+        }
+    }
+
+    /// Merges consecutive lines whose inner points deviate less than the tolerance.
+    fn merge_collinear_lines(&mut self, tolerance: f64) {
+        let mut segments = Vec::with_capacity(self.segments.len());
+        let mut run: Vec<Point> = vec![];
+
+        fn flush_run(run: &mut Vec<Point>, segments: &mut Vec<PathSeg>) {
+            if let (Some(&first), Some(&last)) = (run.first(), run.last()) {
+                segments.push(PathSeg::Line(Line::new(first, last)));
+            }
+            run.clear();
+        }
+
+        for segment in self.segments.drain(..) {
+            match segment {
+                PathSeg::Line(line) => {
+                    if run.is_empty() {
+                        run.extend([line.p0, line.p1]);
+                    } else if is_collinear(run[0], &run[1..], line.p1, tolerance) {
+                        run.push(line.p1);
+                    } else {
+                        flush_run(&mut run, &mut segments);
+                        run.extend([line.p0, line.p1]);
+                    }
+                }
+                segment => {
+                    flush_run(&mut run, &mut segments);
+                    segments.push(segment);
+                }
+            }
+        }
+        flush_run(&mut run, &mut segments);
+
+        self.segments = segments;
+    }
+
+    /// Replaces runs of consecutive curves with fewer curves if possible.
+    fn refit_curves(&mut self, tolerance: f64) {
+        let mut segments = Vec::with_capacity(self.segments.len());
+        let mut run: Vec<PathSeg> = vec![];
+
+        fn flush_run(run: &mut Vec<PathSeg>, segments: &mut Vec<PathSeg>, tolerance: f64) {
+            if run.len() > 1 {
+                let mut bez_path = BezPath::new();
+                bez_path.move_to(run[0].start());
+                for segment in run.iter() {
+                    bez_path.push(segment.as_path_el());
+                }
+
+                let refitted = simplify_bezpath(bez_path, tolerance, &SimplifyOptions::default());
+                if refitted.segments().count() < run.len() {
+                    segments.extend(refitted.segments());
+                    run.clear();
+                    return;
+                }
+            }
+            segments.append(run);
+        }
+
+        for segment in self.segments.drain(..) {
+            match segment {
+                PathSeg::Line(_) => {
+                    flush_run(&mut run, &mut segments, tolerance);
+                    segments.push(segment);
+                }
+                segment => run.push(segment),
+            }
+        }
+        flush_run(&mut run, &mut segments, tolerance);
+
+        self.segments = segments;
+    }
+
+    fn append_to(&self, bez_path: &mut BezPath) {
+        let Some(first) = self.segments.first() else {
+            return;
+        };
+        let start_point = first.start();
+
+        bez_path.move_to(start_point);
+        for (i, segment) in self.segments.iter().enumerate() {
+            let is_last = i + 1 == self.segments.len();
+            // The closing line is implied by closing the path
+            if self.closed
+                && is_last
+                && matches!(segment, PathSeg::Line(line) if line.p1 == start_point)
+            {
+                continue;
+            }
+            bez_path.push(segment.as_path_el());
+        }
+        if self.closed {
+            bez_path.close_path();
+        }
+    }
+}
+
+/// Checks whether all inner points lie within the tolerance of the line from start to end.
+fn is_collinear(start: Point, inner_points: &[Point], end: Point, tolerance: f64) -> bool {
+    let line = Line::new(start, end);
+    inner_points
+        .iter()
+        .all(|&point| line.nearest(point, 1e-9).distance_sq <= tolerance * tolerance)
+}
+
+/// Checks whether a segment lies within the tolerance of a point.
+fn stays_within(segment: PathSeg, point: Point, tolerance: f64) -> bool {
+    let bbox = segment.bounding_box();
+    [
+        Point::new(bbox.x0, bbox.y0),
+        Point::new(bbox.x1, bbox.y0),
+        Point::new(bbox.x0, bbox.y1),
+        Point::new(bbox.x1, bbox.y1),
+    ]
+    .iter()
+    .all(|corner| corner.distance(point) <= tolerance)
+}
+
+fn with_start(segment: PathSeg, p0: Point) -> PathSeg {
+    match segment {
+        PathSeg::Line(line) => PathSeg::Line(Line::new(p0, line.p1)),
+        PathSeg::Quad(quad) => PathSeg::Quad(QuadBez::new(p0, quad.p1, quad.p2)),
+        PathSeg::Cubic(cubic) => PathSeg::Cubic(CubicBez::new(p0, cubic.p1, cubic.p2, cubic.p3)),
+    }
+}
+
+fn with_end(segment: PathSeg, end: Point) -> PathSeg {
+    match segment {
+        PathSeg::Line(line) => PathSeg::Line(Line::new(line.p0, end)),
+        PathSeg::Quad(quad) => PathSeg::Quad(QuadBez::new(quad.p0, quad.p1, end)),
+        PathSeg::Cubic(cubic) => PathSeg::Cubic(CubicBez::new(cubic.p0, cubic.p1, cubic.p2, end)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_collinear_lines() {
+        // Square with redundant points on its edges, starting in the middle of an edge
+        let mut bez_path = BezPath::new();
+        bez_path.move_to((50.0, 0.0));
+        bez_path.line_to((100.0, 0.0));
+        bez_path.line_to((100.0, 30.0));
+        bez_path.line_to((100.05, 60.0));
+        bez_path.line_to((100.0, 100.0));
+        bez_path.line_to((0.0, 100.0));
+        bez_path.line_to((0.0, 0.0));
+        bez_path.line_to((20.0, 0.0));
+        bez_path.close_path();
+
+        let result = simplify_outline(vec![bez_path], 0.1);
+
+        let mut expected = BezPath::new();
+        expected.move_to((100.0, 0.0));
+        expected.line_to((100.0, 100.0));
+        expected.line_to((0.0, 100.0));
+        expected.line_to((0.0, 0.0));
+        expected.close_path();
+
+        assert_eq!(result, vec![expected]);
+    }
+
+    #[test]
+    fn remove_zero_length_segments() {
+        let mut bez_path = BezPath::new();
+        bez_path.move_to((0.0, 0.0));
+        bez_path.line_to((100.0, 0.0));
+        bez_path.line_to((100.0, 0.0));
+        bez_path.quad_to((100.0, 0.0), (100.0, 0.0));
+        bez_path.line_to((50.0, 100.0));
+        bez_path.close_path();
+
+        let result = simplify_outline(vec![bez_path], 0.1);
+
+        let mut expected = BezPath::new();
+        expected.move_to((0.0, 0.0));
+        expected.line_to((100.0, 0.0));
+        expected.line_to((50.0, 100.0));
+        expected.close_path();
+
+        assert_eq!(result, vec![expected]);
+    }
+
+    #[test]
+    fn remove_runs_of_short_segments() {
+        // A circle traced with lines that are each shorter than the tolerance
+        let mut bez_path = BezPath::new();
+        let num_segments = 400;
+        for i in 0..num_segments {
+            let angle = std::f64::consts::TAU * i as f64 / num_segments as f64;
+            let point = Point::new(50.0 * angle.cos(), 50.0 * angle.sin());
+            if i == 0 {
+                bez_path.move_to(point);
+            } else {
+                bez_path.line_to(point);
+            }
+        }
+        bez_path.close_path();
+        let tolerance = 1.0;
+
+        let result = simplify_outline(vec![bez_path.clone()], tolerance);
+
+        assert_eq!(result.len(), 1);
+        let bounds = result[0].bounding_box();
+        let expected = bez_path.bounding_box();
+        for (value, expected) in [
+            (bounds.x0, expected.x0),
+            (bounds.y0, expected.y0),
+            (bounds.x1, expected.x1),
+            (bounds.y1, expected.y1),
+        ] {
+            assert!((value - expected).abs() <= tolerance);
+        }
+        assert!(result[0].segments().count() < num_segments);
+    }
+
+    #[test]
+    fn refit_curves() {
+        // A quarter circle split into many small curves
+        let mut bez_path = BezPath::new();
+        bez_path.move_to((100.0, 0.0));
+        let steps = 16;
+        for i in 1..=steps {
+            let angle0 = std::f64::consts::FRAC_PI_2 * (i - 1) as f64 / steps as f64;
+            let angle1 = std::f64::consts::FRAC_PI_2 * i as f64 / steps as f64;
+            let angle = (angle0 + angle1) / 2.0;
+            let control_radius = 100.0 / ((angle1 - angle0) / 2.0).cos();
+            bez_path.quad_to(
+                (control_radius * angle.cos(), control_radius * angle.sin()),
+                (100.0 * angle1.cos(), 100.0 * angle1.sin()),
+            );
+        }
+        bez_path.line_to((0.0, 0.0));
+        bez_path.close_path();
+
+        let result = simplify_outline(vec![bez_path.clone()], 0.1);
+        let num_segments = result[0].segments().count();
+
+        assert!(num_segments < bez_path.segments().count());
+        assert!((result[0].area() - bez_path.area()).abs() < 100.0);
+    }
+}
@@ -0,0 +1,121 @@
+use i_overlay::{
+    core::{fill_rule::FillRule, overlay_rule::OverlayRule},
+    float::{simplify::SimplifyShape, single::SingleFloatOverlay},
+    mesh::{
+        outline::offset::OutlineOffset,
+        style::{LineJoin, OutlineStyle},
+    },
+};
+use kurbo::{BezPath, Shape};
+
+use super::overlap::{fit_contour, flatten, overlapping_groups, split_subpaths, Polygon};
+
+/// Removes the area covered by other paths, widened by a gap, from outlines.
+///
+/// Holes of the covering paths are part of the removed area, so that only their silhouette
+/// matters. Contours that do not come close to the covering paths are kept as they are, the
+/// others are flattened for the difference and curves are refitted afterwards, so that the
+/// result deviates at most by the given tolerance in path units.
+pub fn knock_out(
+    bez_paths: Vec<BezPath>,
+    cover: &[BezPath],
+    gap: f64,
+    tolerance: f64,
+) -> Vec<BezPath> {
+    let polygons: Vec<Polygon> = split_subpaths(cover)
+        .iter()
+        .map(|subpath| flatten(subpath, tolerance / 4.0))
+        .filter(|polygon| polygon.len() > 2)
+        .collect();
+    let silhouette: Vec<Polygon> = polygons
+        .simplify_shape(FillRule::NonZero)
+        .into_iter()
+        .filter_map(|shape| shape.into_iter().next())
+        .collect();
+    let knockout: Vec<Polygon> = if gap > 0.0 {
+        // Round joins are approximated by segments whose length relative to the gap keeps
+        // the deviation within the tolerance
+        let max_segment_ratio = (2.0 * tolerance / gap).sqrt().min(1.0);
+        let style = OutlineStyle::new(gap).line_join(LineJoin::Round(max_segment_ratio));
+        silhouette.outline(&style).into_iter().flatten().collect()
+    } else {
+        silhouette
+    };
+    let Some(knockout_bbox) = knockout
+        .iter()
+        .flatten()
+        .map(|&[x, y]| kurbo::Rect::from_points((x, y), (x, y)))
+        .reduce(|bbox1, bbox2| bbox1.union(bbox2))
+    else {
+        return bez_paths;
+    };
+
+    // The knocked out area is grouped with the contours it may touch
+    let mut subpaths = split_subpaths(&bez_paths);
+    subpaths.push(knockout_bbox.to_path(0.1));
+    let knockout_index = subpaths.len() - 1;
+
+    let mut result = vec![];
+    for group in overlapping_groups(&subpaths) {
+        if !group.contains(&knockout_index) {
+            result.extend(group.iter().map(|&index| subpaths[index].clone()));
+            continue;
+        }
+
+        let polygons: Vec<Polygon> = group
+            .iter()
+            .filter(|&&index| index != knockout_index)
+            .map(|&index| flatten(&subpaths[index], tolerance / 4.0))
+            .filter(|polygon| polygon.len() > 2)
+            .collect();
+        let shapes = polygons.overlay(&knockout, OverlayRule::Difference, FillRule::NonZero);
+        result.extend(
+            shapes
+                .iter()
+                .flatten()
+                .map(|contour| fit_contour(contour, tolerance)),
+        );
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kurbo::{Circle, Rect};
+
+    #[test]
+    fn knock_out_with_gap() {
+        let bez_paths = vec![
+            Rect::new(0.0, 0.0, 100.0, 100.0).to_path(0.1),
+            Rect::new(200.0, 0.0, 220.0, 20.0).to_path(0.1),
+        ];
+        let mut badge = Circle::new((50.0, 50.0), 20.0).to_path(0.1);
+        badge.extend(
+            Circle::new((50.0, 50.0), 10.0)
+                .to_path(0.1)
+                .reverse_subpaths(),
+        );
+
+        let result = knock_out(bez_paths.clone(), &[badge], 10.0, 0.05);
+
+        // The distant square is kept and the hole of the badge is knocked out as well
+        assert_eq!(result.len(), 3);
+        assert!(result.contains(&bez_paths[1]));
+        let area: f64 = result
+            .iter()
+            .filter(|&bez_path| *bez_path != bez_paths[1])
+            .map(|bez_path| bez_path.area())
+            .sum();
+        let expected_area = 100.0 * 100.0 - std::f64::consts::PI * 30.0 * 30.0;
+        assert!((area.abs() - expected_area).abs() < 10.0, "area {area}");
+    }
+
+    #[test]
+    fn nothing_to_knock_out() {
+        let bez_paths = vec![Rect::new(0.0, 0.0, 100.0, 100.0).to_path(0.1)];
+
+        assert_eq!(knock_out(bez_paths.clone(), &[], 10.0, 0.05), bez_paths);
+    }
+}
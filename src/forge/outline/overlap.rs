@@ -0,0 +1,270 @@
+use i_overlay::{core::fill_rule::FillRule, float::simplify::SimplifyShape};
+use kurbo::{
+    simplify::{simplify_bezpath, SimplifyOptions},
+    BezPath, PathEl, Point, Rect, Shape, Vec2,
+};
+
+use super::{is_collinear, Subpath};
+
+/// Maximum angle in radians between two flattened segments that are refitted as one curve
+const MAX_SMOOTH_ANGLE: f64 = 0.5;
+
+pub(super) type Polygon = Vec<[f64; 2]>;
+
+/// Merges overlapping contours into a minimal set of non-overlapping contours.
+///
+/// The filled area is determined with the non-zero winding rule like in TrueType.
+/// Overlapping contours are flattened for the union and curves are refitted afterwards,
+/// so that the result deviates at most by the given tolerance in path units.
+/// Contours that do not overlap any other contour are kept as they are.
+pub fn remove_overlaps(bez_paths: Vec<BezPath>, tolerance: f64) -> Vec<BezPath> {
+    let subpaths = split_subpaths(&bez_paths);
+
+    let mut result = vec![];
+    for group in overlapping_groups(&subpaths) {
+        if let [index] = group[..] {
+            result.push(subpaths[index].clone());
+            continue;
+        }
+
+        // Flattening and refitting share the tolerance
+        let polygons: Vec<Polygon> = group
+            .iter()
+            .map(|&index| flatten(&subpaths[index], tolerance / 4.0))
+            .filter(|polygon| polygon.len() > 2)
+            .collect();
+        let shapes = polygons.simplify_shape(FillRule::NonZero);
+
+        // Without overlaps the union covers the same area as the contours themselves
+        let area: f64 = polygons.iter().map(|polygon| signed_area(polygon)).sum();
+        let union_area: f64 = shapes
+            .iter()
+            .flatten()
+            .map(|contour| signed_area(contour))
+            .sum();
+        let perimeter: f64 = group
+            .iter()
+            .map(|&index| subpaths[index].perimeter(0.1))
+            .sum();
+        if (area.abs() - union_area.abs()).abs() <= tolerance * perimeter {
+            result.extend(group.iter().map(|&index| subpaths[index].clone()));
+            continue;
+        }
+
+        result.extend(
+            shapes
+                .iter()
+                .flatten()
+                .map(|contour| fit_contour(contour, tolerance)),
+        );
+    }
+
+    result
+}
+
+/// Splits paths so that each path consists of a single subpath.
+pub(super) fn split_subpaths(bez_paths: &[BezPath]) -> Vec<BezPath> {
+    bez_paths
+        .iter()
+        .flat_map(Subpath::split)
+        .map(|subpath| {
+            let mut bez_path = BezPath::new();
+            subpath.append_to(&mut bez_path);
+            bez_path
+        })
+        .collect()
+}
+
+/// Groups the indices of paths whose bounding boxes intersect, directly or transitively.
+pub(super) fn overlapping_groups(bez_paths: &[BezPath]) -> Vec<Vec<usize>> {
+    let bboxes: Vec<Rect> = bez_paths.iter().map(|path| path.bounding_box()).collect();
+    let mut group_ids: Vec<usize> = (0..bboxes.len()).collect();
+
+    fn root(group_ids: &mut [usize], mut index: usize) -> usize {
+        while group_ids[index] != index {
+            group_ids[index] = group_ids[group_ids[index]];
+            index = group_ids[index];
+        }
+        index
+    }
+
+    for i in 0..bboxes.len() {
+        for j in (i + 1)..bboxes.len() {
+            if bboxes[i].overlaps(bboxes[j]) {
+                let (root_i, root_j) = (root(&mut group_ids, i), root(&mut group_ids, j));
+                group_ids[root_j] = root_i;
+            }
+        }
+    }
+
+    let mut groups: Vec<Vec<usize>> = vec![];
+    let mut group_indices = vec![usize::MAX; bboxes.len()];
+    for index in 0..bboxes.len() {
+        let group_id = root(&mut group_ids, index);
+        if group_indices[group_id] == usize::MAX {
+            group_indices[group_id] = groups.len();
+            groups.push(vec![]);
+        }
+        groups[group_indices[group_id]].push(index);
+    }
+
+    groups
+}
+
+/// Approximates a closed path with a polygon.
+pub(super) fn flatten(bez_path: &BezPath, tolerance: f64) -> Polygon {
+    let mut polygon: Polygon = vec![];
+    kurbo::flatten(bez_path, tolerance, |element| {
+        if let PathEl::MoveTo(point) | PathEl::LineTo(point) = element {
+            if polygon.last() != Some(&[point.x, point.y]) {
+                polygon.push([point.x, point.y]);
+            }
+        }
+    });
+    if polygon.len() > 1 && polygon.first() == polygon.last() {
+        polygon.pop();
+    }
+
+    polygon
+}
+
+pub(super) fn signed_area(polygon: &[[f64; 2]]) -> f64 {
+    let len = polygon.len();
+    (0..len)
+        .map(|i| {
+            let [x0, y0] = polygon[i];
+            let [x1, y1] = polygon[(i + 1) % len];
+            x0 * y1 - x1 * y0
+        })
+        .sum::<f64>()
+        / 2.0
+}
+
+/// Converts a polygon back into a closed path where flattened curves are refitted as curves.
+pub(super) fn fit_contour(polygon: &[[f64; 2]], tolerance: f64) -> BezPath {
+    let points: Vec<Point> = polygon.iter().map(|&[x, y]| Point::new(x, y)).collect();
+    let len = points.len();
+
+    let is_corner = |i: usize| {
+        let incoming = points[i] - points[(i + len - 1) % len];
+        let outgoing = points[(i + 1) % len] - points[i];
+        angle_between(incoming, outgoing) > MAX_SMOOTH_ANGLE
+    };
+    let start = (0..len).find(|&i| is_corner(i)).unwrap_or(0);
+
+    let mut bez_path = BezPath::new();
+    bez_path.move_to(points[start]);
+
+    // Split the contour into runs between corners
+    let mut run = vec![points[start]];
+    for offset in 1..=len {
+        let i = (start + offset) % len;
+        run.push(points[i]);
+        if offset == len || is_corner(i) {
+            append_run(&mut bez_path, &run, tolerance);
+            run = vec![points[i]];
+        }
+    }
+    // The closing line is implied by closing the path
+    if bez_path.elements().last() == Some(&PathEl::LineTo(points[start])) {
+        bez_path.pop();
+    }
+    bez_path.close_path();
+
+    bez_path
+}
+
+/// Appends a run of points without corners either as a line or as fitted curves.
+fn append_run(bez_path: &mut BezPath, run: &[Point], tolerance: f64) {
+    let (first, last) = (run[0], run[run.len() - 1]);
+
+    if run.len() <= 3 || is_collinear(first, &run[1..run.len() - 1], last, tolerance) {
+        for &point in &run[1..] {
+            bez_path.line_to(point);
+        }
+        return;
+    }
+
+    let mut polyline = BezPath::new();
+    polyline.move_to(first);
+    for &point in &run[1..] {
+        polyline.line_to(point);
+    }
+
+    // Corners are already handled, so the whole run is treated as smooth
+    let options = SimplifyOptions::default().angle_thresh(f64::INFINITY);
+    let fitted = simplify_bezpath(polyline, tolerance, &options);
+    for element in fitted.elements() {
+        if !matches!(element, PathEl::MoveTo(_)) {
+            bez_path.push(*element);
+        }
+    }
+}
+
+fn angle_between(v1: Vec2, v2: Vec2) -> f64 {
+    v1.cross(v2).atan2(v1.dot(v2)).abs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kurbo::Circle;
+
+    #[test]
+    fn union_of_overlapping_rectangles() {
+        let bez_paths = vec![
+            Rect::new(10.0, 10.0, 110.0, 60.0).to_path(0.1),
+            Rect::new(40.0, 40.0, 80.0, 80.0).to_path(0.1),
+        ];
+
+        let result = remove_overlaps(bez_paths, 0.05);
+
+        assert_eq!(result.len(), 1);
+        let num_lines = result[0]
+            .elements()
+            .iter()
+            .filter(|element| matches!(element, PathEl::LineTo(_)))
+            .count();
+        assert_eq!(num_lines, 7);
+        assert_eq!(result[0].bounding_box(), Rect::new(10.0, 10.0, 110.0, 80.0));
+        assert!((result[0].area().abs() - (100.0 * 50.0 + 40.0 * 20.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn separate_contours_are_kept() {
+        let mut ring = Circle::new((50.0, 50.0), 40.0).to_path(0.1);
+        // Hole with opposite orientation
+        ring.extend(
+            Circle::new((50.0, 50.0), 20.0)
+                .to_path(0.1)
+                .reverse_subpaths(),
+        );
+        let square = Rect::new(100.0, 0.0, 120.0, 20.0).to_path(0.1);
+        let bez_paths = vec![ring, square];
+
+        let result = remove_overlaps(bez_paths, 0.05);
+
+        assert_eq!(result.len(), 3);
+        assert!(result.iter().any(|bez_path| bez_path
+            .elements()
+            .iter()
+            .any(|element| matches!(element, PathEl::CurveTo(..)))));
+    }
+
+    #[test]
+    fn union_keeps_curves() {
+        let bez_paths = vec![
+            Circle::new((40.0, 50.0), 30.0).to_path(0.1),
+            Circle::new((60.0, 50.0), 30.0).to_path(0.1),
+        ];
+
+        let result = remove_overlaps(bez_paths, 0.04);
+
+        assert_eq!(result.len(), 1);
+        assert!(result[0].elements().len() < 30);
+        assert!(result[0]
+            .elements()
+            .iter()
+            .any(|element| matches!(element, PathEl::CurveTo(..))));
+    }
+}
@@ -0,0 +1,112 @@
+use kurbo::{BezPath, PathEl};
+use std::fmt;
+
+/// Summary of a font build.
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    /// Number of outline points before and after outline simplification for each icon.
+    ///
+    /// Empty if outline simplification is disabled.
+    pub point_savings: Vec<PointSavings>,
+
+    /// Icons of the baseline font that are missing from the new font.
+    ///
+    /// Empty if no baseline font is given.
+    pub removed_icons: Vec<String>,
+
+    /// Icons whose glyphs are identical to the glyph of a previous icon, which they share.
+    ///
+    /// Each entry consists of the ID of the icon the glyph is named after and the IDs of
+    /// its duplicates.
+    pub merged_duplicates: Vec<(String, Vec<String>)>,
+
+    /// Tags of icons for building a search index, see [`ManifestIcon::tags`].
+    ///
+    /// Empty if no manifest is given.
+    ///
+    /// [`ManifestIcon::tags`]: super::ManifestIcon::tags
+    pub tags: Vec<(String, Vec<String>)>,
+}
+
+/// Number of outline points of an icon before and after outline simplification.
+///
+/// Points are counted on the Bézier paths including control points, before cubic curves are
+/// converted to quadratic splines.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PointSavings {
+    /// ID of the simplified icon
+    pub icon_id: String,
+    /// Number of points of the original outlines
+    pub points_before: usize,
+    /// Number of points of the simplified outlines
+    pub points_after: usize,
+}
+
+impl PointSavings {
+    pub(crate) fn new(icon_id: &str, paths_before: &[BezPath], paths_after: &[BezPath]) -> Self {
+        Self {
+            icon_id: icon_id.to_string(),
+            points_before: num_points(paths_before),
+            points_after: num_points(paths_after),
+        }
+    }
+}
+
+impl Report {
+    /// Returns the total number of glyph points removed by outline simplification.
+    pub fn points_saved(&self) -> usize {
+        self.point_savings
+            .iter()
+            .map(|savings| savings.points_before.saturating_sub(savings.points_after))
+            .sum()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.point_savings.is_empty() {
+            let points_before: usize = self.point_savings.iter().map(|s| s.points_before).sum();
+            let points_after: usize = self.point_savings.iter().map(|s| s.points_after).sum();
+            writeln!(
+                f,
+                "outline simplification: {points_before} -> {points_after} points"
+            )?;
+            for savings in &self.point_savings {
+                if savings.points_after < savings.points_before {
+                    writeln!(
+                        f,
+                        "  {}: {} -> {} points",
+                        savings.icon_id, savings.points_before, savings.points_after
+                    )?;
+                }
+            }
+        }
+        if !self.merged_duplicates.is_empty() {
+            writeln!(f, "merged duplicates:")?;
+            for (icon_id, duplicates) in &self.merged_duplicates {
+                writeln!(f, "  {icon_id}: {}", duplicates.join(", "))?;
+            }
+        }
+        if !self.removed_icons.is_empty() {
+            writeln!(f, "removed icons:")?;
+            for icon_id in &self.removed_icons {
+                writeln!(f, "  {icon_id}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn num_points(bez_paths: &[BezPath]) -> usize {
+    bez_paths
+        .iter()
+        .flat_map(|bez_path| bez_path.elements())
+        .map(|element| match element {
+            PathEl::MoveTo(_) | PathEl::LineTo(_) => 1,
+            PathEl::QuadTo(..) => 2,
+            PathEl::CurveTo(..) => 3,
+            PathEl::ClosePath => 0,
+        })
+        .sum()
+}
@@ -1,36 +1,529 @@
-use kurbo::BezPath;
-use usvg::{Group, Node, Options, Tree};
+use kurbo::{Affine, BezPath, Point, Rect};
+use roxmltree::Document;
+use std::{
+    collections::{HashMap, HashSet},
+    f64::consts::SQRT_2,
+    ops::Range,
+};
+use usvg::{Group, Node, Path, Tree};
 
 mod bezier;
 
-pub use self::bezier::bounding_box;
+pub use self::bezier::{bounding_box, cubic_to_quadratic_points};
 
-use self::bezier::process_svg_path;
-use crate::Error;
+use self::bezier::{is_visible_paint, process_svg_path, visible_bounds};
+use crate::{
+    forge::{
+        color::{Color, ColorStop, Extend, Gradient, GradientKind, BLACK, CURRENT_COLOR},
+        font::scale_factor,
+        ColorFormat, Options, Paint,
+    },
+    Error,
+};
+
+/// Properties whose value `currentColor` is replaced to recognize the foreground color
+const CURRENT_COLOR_PROPERTIES: [&str; 4] = ["fill", "stroke", "color", "stop-color"];
+
+/// Outlines of an SVG icon in SVG user units
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgIcon {
+    pub bez_paths: Vec<BezPath>,
+    /// Paint of each path
+    pub paints: Vec<Paint>,
+    /// View box of the SVG document, or its size if it has no view box
+    pub view_box: Rect,
+}
 
 /// Simplifies an SVG expression with `usvg` and returns a list of Bézier curves.
-/// All cubic curve segments are replaced with quadratic segments.
-pub fn simplify_svg(svg_data: String) -> Result<Vec<BezPath>, Error> {
+/// Strokes are converted to outlines, cubic curve segments are kept.
+///
+/// Invisible elements and elements excluded by the given options are skipped.
+pub fn simplify_svg(svg_data: String, options: &Options) -> Result<SvgIcon, Error> {
+    let svg_data = remove_excluded_elements(svg_data, options)?;
+
     // Simplify SVG with usvg
-    let opt = Options::default();
-    let tree = Tree::from_str(&svg_data, &opt)?;
+    let opt = usvg::Options::default();
+    let mut tree = Tree::from_str(&svg_data, &opt)?;
+    let mut foreground = None;
+    if options.color_format.is_some() {
+        if let Some((marked_svg_data, marker)) = mark_current_color(&svg_data, &tree)? {
+            tree = Tree::from_str(&marked_svg_data, &opt)?;
+            foreground = Some(marker);
+        }
+    }
+    let view_box = parse_view_box(&svg_data).unwrap_or_else(|| {
+        Rect::new(
+            0.0,
+            0.0,
+            tree.size().width() as f64,
+            tree.size().height() as f64,
+        )
+    });
+
+    let focal_radii = match options.color_format {
+        Some(ColorFormat::ColrV1) => focal_radii(&svg_data, view_box, &tree),
+        Some(ColorFormat::ColrV0) | None => HashMap::new(),
+    };
+
+    let mut svg_paths = vec![];
+    visit_group(tree.root(), 1.0, &mut svg_paths);
+
+    // Estimate the scale of the glyph to express the stroke tolerance in font units
+    let bounds = svg_paths
+        .iter()
+        .filter_map(|(svg_path, _)| visible_bounds(svg_path))
+        .reduce(|bounds1, bounds2| bounds1.union(bounds2))
+        .unwrap_or(Rect::ZERO);
+    if bounds.size().max_side() <= 0.0 {
+        return Ok(SvgIcon {
+            bez_paths: vec![],
+            paints: vec![],
+            view_box,
+        });
+    }
+    let stroke_tolerance = options.stroke_tolerance / scale_factor(bounds, view_box, options);
+
+    let (bez_paths, mut paints): (Vec<_>, Vec<_>) = svg_paths
+        .into_iter()
+        .filter_map(|(svg_path, opacity)| {
+            let bez_path = process_svg_path(svg_path, stroke_tolerance)?;
+            let paint = svg_paint(
+                svg_path,
+                opacity,
+                options.color_format,
+                foreground,
+                &focal_radii,
+            );
+            Some((bez_path, paint))
+        })
+        .unzip();
+    // Icons that are only painted in black, e.g. because they omit the fill, are monochrome
+    if paints
+        .iter()
+        .all(|paint| *paint == Paint::FOREGROUND || *paint == Paint::Solid(BLACK))
+    {
+        paints.fill(Paint::FOREGROUND);
+    }
+
+    Ok(SvgIcon {
+        bez_paths,
+        paints,
+        view_box,
+    })
+}
+
+/// Reads the `viewBox` attribute of the root element.
+///
+/// Returns `None` if the attribute is missing or invalid.
+fn parse_view_box(svg_data: &str) -> Option<Rect> {
+    let document = Document::parse(svg_data).ok()?;
+    let values: Vec<f64> = document
+        .root_element()
+        .attribute("viewBox")?
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|value| !value.is_empty())
+        .map(|value| value.parse().ok())
+        .collect::<Option<_>>()?;
+
+    match values[..] {
+        [x, y, width, height] if width > 0.0 && height > 0.0 => {
+            Some(Rect::new(x, y, x + width, y + height))
+        }
+        _ => None,
+    }
+}
+
+/// Removes elements whose ID or class is excluded by the options from the SVG source.
+fn remove_excluded_elements(svg_data: String, options: &Options) -> Result<String, Error> {
+    if options.exclude_ids.is_empty() && options.exclude_classes.is_empty() {
+        return Ok(svg_data);
+    }
+
+    let document = Document::parse(&svg_data).map_err(usvg::Error::ParsingFailed)?;
+
+    let mut excluded_ranges = vec![];
+    let mut nodes = document.root().descendants();
+    while let Some(node) = nodes.next() {
+        if node.is_element()
+            && options.excludes_element(node.attribute("id"), node.attribute("class"))
+        {
+            excluded_ranges.push(node.range());
+            // Children are removed together with their parent
+            let end = node.range().end;
+            while nodes
+                .clone()
+                .next()
+                .is_some_and(|next| next.range().start < end)
+            {
+                nodes.next();
+            }
+        }
+    }
+
+    if excluded_ranges.is_empty() {
+        return Ok(svg_data);
+    }
+
+    let mut result = String::with_capacity(svg_data.len());
+    let mut position = 0;
+    for range in excluded_ranges {
+        result.push_str(&svg_data[position..range.start]);
+        position = range.end;
+    }
+    result.push_str(&svg_data[position..]);
+
+    Ok(result)
+}
+
+/// Replaces the keyword `currentColor` in the paint properties of elements with a color that
+/// the parsed icon does not use otherwise, so that it is recognized as the foreground color
+/// after `usvg` has resolved it.
+///
+/// Returns the modified SVG source and the color, or `None` if no property uses the keyword.
+fn mark_current_color(svg_data: &str, tree: &Tree) -> Result<Option<(String, Color)>, Error> {
+    let document = Document::parse(svg_data).map_err(usvg::Error::ParsingFailed)?;
+
+    let mut ranges = vec![];
+    for node in document.descendants().filter(|node| node.is_element()) {
+        for attribute in node.attributes() {
+            let range = attribute.range_value();
+            if attribute.name() == "style" {
+                ranges.extend(current_color_declarations(
+                    &svg_data[range.clone()],
+                    range.start,
+                ));
+            } else if CURRENT_COLOR_PROPERTIES.contains(&attribute.name())
+                && is_current_color(attribute.value())
+            {
+                ranges.push(range);
+            }
+        }
+    }
+    if ranges.is_empty() {
+        return Ok(None);
+    }
+
+    let used_colors = used_colors(tree);
+    let start = u32::from_be_bytes([
+        0,
+        CURRENT_COLOR.red,
+        CURRENT_COLOR.green,
+        CURRENT_COLOR.blue,
+    ]);
+    let marker = (0..=0xFFFFFF_u32)
+        .map(|step| {
+            let [_, red, green, blue] = ((start + step) & 0xFFFFFF).to_be_bytes();
+            Color {
+                red,
+                green,
+                blue,
+                alpha: 0xFF,
+            }
+        })
+        .find(|color| !used_colors.contains(color))
+        .unwrap_or(CURRENT_COLOR);
+    let replacement = format!("#{:02x}{:02x}{:02x}", marker.red, marker.green, marker.blue);
+
+    let mut result = String::with_capacity(svg_data.len());
+    let mut position = 0;
+    for range in ranges {
+        result.push_str(&svg_data[position..range.start]);
+        result.push_str(&replacement);
+        position = range.end;
+    }
+    result.push_str(&svg_data[position..]);
+
+    Ok(Some((result, marker)))
+}
+
+/// Returns the ranges of the values of `currentColor` declarations of paint properties in the
+/// value of a `style` attribute that starts at the given offset.
+fn current_color_declarations(style: &str, offset: usize) -> Vec<Range<usize>> {
+    let mut ranges = vec![];
+    let mut start = offset;
+    for declaration in style.split(';') {
+        if let Some((property, value)) = declaration.split_once(':') {
+            if CURRENT_COLOR_PROPERTIES.contains(&property.trim()) && is_current_color(value) {
+                let value_start = start + property.len() + 1;
+                ranges.push(value_start..value_start + value.len());
+            }
+        }
+        start += declaration.len() + 1;
+    }
+
+    ranges
+}
+
+/// Returns `true` if the value is the case-insensitive keyword `currentColor`.
+fn is_current_color(value: &str) -> bool {
+    value.trim().eq_ignore_ascii_case("currentColor")
+}
+
+/// Returns the opaque colors of all fills, strokes and gradient stops of an icon.
+fn used_colors(tree: &Tree) -> HashSet<Color> {
+    let mut svg_paths = vec![];
+    visit_group(tree.root(), 1.0, &mut svg_paths);
+
+    let color = |color: usvg::Color| Color {
+        red: color.red,
+        green: color.green,
+        blue: color.blue,
+        alpha: 0xFF,
+    };
+    let mut colors = HashSet::new();
+    let paints = svg_paths.iter().flat_map(|(svg_path, _)| {
+        [
+            svg_path.fill().map(|fill| fill.paint()),
+            svg_path.stroke().map(|stroke| stroke.paint()),
+        ]
+    });
+    for paint in paints.flatten() {
+        match paint {
+            usvg::Paint::Color(paint_color) => {
+                colors.insert(color(*paint_color));
+            }
+            usvg::Paint::LinearGradient(gradient) => {
+                colors.extend(gradient.stops().iter().map(|stop| color(stop.color())));
+            }
+            usvg::Paint::RadialGradient(gradient) => {
+                colors.extend(gradient.stops().iter().map(|stop| color(stop.color())));
+            }
+            usvg::Paint::Pattern(_) => {}
+        }
+    }
+
+    colors
+}
+
+/// Returns the focal radii of radial gradients of the parsed icon by their ID, since `usvg`
+/// does not support the `fr` attribute.
+///
+/// Gradients that `usvg` copies under a new ID are recognized by their other attributes.
+fn focal_radii(svg_data: &str, view_box: Rect, tree: &Tree) -> HashMap<String, f64> {
+    let Ok(document) = Document::parse(svg_data) else {
+        return HashMap::new();
+    };
+    let gradients: HashMap<&str, roxmltree::Node> = document
+        .descendants()
+        .filter(|node| matches!(node.tag_name().name(), "linearGradient" | "radialGradient"))
+        .filter_map(|node| Some((node.attribute("id")?, node)))
+        .collect();
+
+    let source_radii: HashMap<&str, f64> = gradients
+        .iter()
+        .filter(|(_, node)| node.tag_name().name() == "radialGradient")
+        .filter_map(|(&id, &node)| {
+            let value = gradient_attribute(node, "fr", &gradients)?.trim();
+            let radius = match value.strip_suffix('%') {
+                Some(percentage) => {
+                    let fraction = percentage.trim().parse::<f64>().ok()? / 100.0;
+                    match gradient_attribute(node, "gradientUnits", &gradients) {
+                        // Percentages refer to the normalized diagonal of the view box
+                        Some("userSpaceOnUse") => {
+                            fraction * view_box.width().hypot(view_box.height()) / SQRT_2
+                        }
+                        _ => fraction,
+                    }
+                }
+                None => value.parse().ok()?,
+            };
+            Some((id, radius))
+        })
+        .collect();
+    if source_radii.is_empty() {
+        return HashMap::new();
+    }
 
-    let mut bez_paths = vec![];
-    visit_group(tree.root(), &mut bez_paths);
+    let originals: Vec<(&usvg::RadialGradient, f64)> = tree
+        .radial_gradients()
+        .iter()
+        .filter_map(|gradient| Some((gradient.as_ref(), *source_radii.get(gradient.id())?)))
+        .collect();
+    tree.radial_gradients()
+        .iter()
+        .filter_map(|gradient| {
+            let radius = source_radii.get(gradient.id()).copied().or_else(|| {
+                originals
+                    .iter()
+                    .find(|(original, _)| is_gradient_copy(gradient, original))
+                    .map(|&(_, radius)| radius)
+            })?;
+            Some((gradient.id().to_string(), radius))
+        })
+        .collect()
+}
 
-    Ok(bez_paths)
+/// Returns the value of a gradient attribute, which may be inherited from the gradient that
+/// is referenced by `href`.
+fn gradient_attribute<'a>(
+    node: roxmltree::Node<'a, 'a>,
+    name: &str,
+    gradients: &HashMap<&str, roxmltree::Node<'a, 'a>>,
+) -> Option<&'a str> {
+    let mut node = node;
+    // Cyclic references end after visiting each gradient once
+    for _ in 0..=gradients.len() {
+        if let Some(value) = node.attribute(name) {
+            return Some(value);
+        }
+        let href = node
+            .attributes()
+            .find(|attribute| attribute.name() == "href")?;
+        node = *gradients.get(href.value().strip_prefix('#')?)?;
+    }
+
+    None
+}
+
+/// Returns `true` if the radial gradients only differ by their ID and their transform.
+fn is_gradient_copy(gradient: &usvg::RadialGradient, other: &usvg::RadialGradient) -> bool {
+    gradient.cx() == other.cx()
+        && gradient.cy() == other.cy()
+        && gradient.r() == other.r()
+        && gradient.fx() == other.fx()
+        && gradient.fy() == other.fy()
+        && gradient.spread_method() == other.spread_method()
+        && gradient.stops().len() == other.stops().len()
+        && gradient
+            .stops()
+            .iter()
+            .zip(other.stops())
+            .all(|(stop, other)| {
+                stop.offset() == other.offset()
+                    && stop.color() == other.color()
+                    && stop.opacity() == other.opacity()
+            })
 }
 
-fn visit_group(group: &Group, bez_paths: &mut Vec<BezPath>) {
+/// Returns the paint of a path with the given opacity of its groups.
+///
+/// Strokes take precedence over fills, since both become a single outline. Gradients are
+/// reduced to their average color and opacity is only kept for solid colors unless the color
+/// format supports them.
+fn svg_paint(
+    svg_path: &Path,
+    opacity: f32,
+    color_format: Option<ColorFormat>,
+    foreground: Option<Color>,
+    focal_radii: &HashMap<String, f64>,
+) -> Paint {
+    let (paint, paint_opacity) = match svg_path
+        .stroke()
+        .filter(|stroke| is_visible_paint(stroke.paint(), stroke.opacity()))
+    {
+        Some(stroke) => (stroke.paint(), stroke.opacity()),
+        None => match svg_path.fill() {
+            Some(fill) => (fill.paint(), fill.opacity()),
+            None => return Paint::FOREGROUND,
+        },
+    };
+    let opacity = opacity * paint_opacity.get();
+
+    let paint = match paint {
+        usvg::Paint::Color(color) => {
+            let color = Color {
+                red: color.red,
+                green: color.green,
+                blue: color.blue,
+                alpha: 0xFF,
+            };
+            if Some(color) == foreground {
+                return match color_format {
+                    Some(ColorFormat::ColrV1) => Paint::Foreground {
+                        alpha: color.with_opacity(opacity).alpha,
+                    },
+                    Some(ColorFormat::ColrV0) | None => Paint::FOREGROUND,
+                };
+            }
+            Paint::Solid(color.with_opacity(opacity))
+        }
+        usvg::Paint::LinearGradient(gradient) => {
+            let kind = GradientKind::Linear {
+                start: Point::new(gradient.x1() as f64, gradient.y1() as f64),
+                end: Point::new(gradient.x2() as f64, gradient.y2() as f64),
+            };
+            Paint::Gradient(svg_gradient(gradient, kind, opacity, foreground))
+        }
+        usvg::Paint::RadialGradient(gradient) => {
+            let kind = GradientKind::Radial {
+                start: Point::new(gradient.fx() as f64, gradient.fy() as f64),
+                start_radius: focal_radii.get(gradient.id()).copied().unwrap_or(0.0),
+                end: Point::new(gradient.cx() as f64, gradient.cy() as f64),
+                end_radius: gradient.r().get() as f64,
+            };
+            Paint::Gradient(svg_gradient(gradient, kind, opacity, foreground))
+        }
+        usvg::Paint::Pattern(_) => return Paint::FOREGROUND,
+    };
+
+    match color_format {
+        Some(ColorFormat::ColrV1) => paint,
+        Some(ColorFormat::ColrV0) | None => paint.flattened(),
+    }
+}
+
+/// Converts an usvg gradient whose units are resolved to the coordinates of the path.
+///
+/// Stops with the color that marks `currentColor` get the foreground color.
+fn svg_gradient(
+    gradient: &usvg::BaseGradient,
+    kind: GradientKind,
+    opacity: f32,
+    foreground: Option<Color>,
+) -> Gradient {
+    let stops = gradient
+        .stops()
+        .iter()
+        .map(|stop| {
+            let color = Color {
+                red: stop.color().red,
+                green: stop.color().green,
+                blue: stop.color().blue,
+                alpha: 0xFF,
+            };
+            ColorStop {
+                offset: stop.offset().get(),
+                color: color.with_opacity(stop.opacity().get() * opacity),
+                foreground: Some(color) == foreground,
+            }
+        })
+        .collect();
+    let extend = match gradient.spread_method() {
+        usvg::SpreadMethod::Pad => Extend::Pad,
+        usvg::SpreadMethod::Reflect => Extend::Reflect,
+        usvg::SpreadMethod::Repeat => Extend::Repeat,
+    };
+    let transform = gradient.transform();
+
+    Gradient {
+        kind,
+        stops,
+        extend,
+        transform: Affine::new(
+            [
+                transform.sx,
+                transform.ky,
+                transform.kx,
+                transform.sy,
+                transform.tx,
+                transform.ty,
+            ]
+            .map(f64::from),
+        ),
+    }
+}
+
+fn visit_group<'a>(group: &'a Group, opacity: f32, svg_paths: &mut Vec<(&'a Path, f32)>) {
     for node in group.children() {
         match *node {
             Node::Path(ref svg_path) => {
-                if let Some(path) = process_svg_path(svg_path) {
-                    bez_paths.push(path);
-                }
+                svg_paths.push((svg_path, opacity));
             }
             Node::Group(ref group) => {
-                visit_group(group, bez_paths);
+                // Fully transparent groups do not contribute to the glyph
+                if group.opacity().get() > 0.0 {
+                    visit_group(group, opacity * group.opacity().get(), svg_paths);
+                }
             }
             Node::Text(ref _text) => {}
             Node::Image(ref _image) => {}
@@ -41,6 +534,7 @@ fn visit_group(group: &Group, bez_paths: &mut Vec<BezPath>) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::forge::ColorFormat;
     use kurbo::Rect;
 
     #[test]
@@ -52,8 +546,8 @@ mod tests {
             </svg>"#
             .to_string();
 
-        let result = simplify_svg(svg).expect("failed to simplify SVG");
-        let bbox = bounding_box(&result);
+        let result = simplify_svg(svg, &Options::default()).expect("failed to simplify SVG");
+        let bbox = bounding_box(&result.bez_paths);
 
         assert_eq!(bbox, Rect::new(10.0, 10.0, 110.0, 60.0));
     }
@@ -68,9 +562,252 @@ mod tests {
             </svg>"#
             .to_string();
 
-        let result = simplify_svg(svg).expect("failed to simplify SVG");
-        let bbox = bounding_box(&result);
+        let result = simplify_svg(svg, &Options::default()).expect("failed to simplify SVG");
+        let bbox = bounding_box(&result.bez_paths);
 
         assert_eq!(bbox, Rect::new(10.0, 10.0, 110.0, 80.0));
     }
+
+    #[test]
+    fn svg_invisible_elements() {
+        // A transparent frame and hidden helpers around a visible rectangle
+        let svg = r#"
+            <svg xmlns="http://www.w3.org/2000/svg" width="24" height="24">
+                <rect width="24" height="24" opacity="0"/>
+                <rect width="24" height="24" fill-opacity="0"/>
+                <rect width="24" height="24" fill="transparent"/>
+                <rect width="24" height="24" fill="none" stroke="black" stroke-opacity="0"/>
+                <rect width="24" height="24" visibility="hidden"/>
+                <g opacity="0"><rect width="24" height="24"/></g>
+                <rect x="4" y="4" width="16" height="16"/>
+            </svg>"#
+            .to_string();
+
+        let result = simplify_svg(svg, &Options::default()).expect("failed to simplify SVG");
+
+        assert_eq!(result.bez_paths.len(), 1);
+        assert_eq!(
+            bounding_box(&result.bez_paths),
+            Rect::new(4.0, 4.0, 20.0, 20.0)
+        );
+    }
+
+    #[test]
+    fn svg_excluded_elements() {
+        let svg = r#"
+            <svg xmlns="http://www.w3.org/2000/svg" width="24" height="24">
+                <rect id="frame" width="24" height="24"/>
+                <g id="guides-layer">
+                    <rect x="2" y="2" width="20" height="20"/>
+                </g>
+                <rect class="icon helper" x="1" y="1" width="22" height="22"/>
+                <rect id="icon" x="4" y="4" width="16" height="16"/>
+            </svg>"#
+            .to_string();
+
+        let options = Options {
+            exclude_ids: vec!["frame".to_string(), "guides*".to_string()],
+            exclude_classes: vec!["helper".to_string()],
+            ..Options::default()
+        };
+        let result = simplify_svg(svg, &options).expect("failed to simplify SVG");
+
+        assert_eq!(result.bez_paths.len(), 1);
+        assert_eq!(
+            bounding_box(&result.bez_paths),
+            Rect::new(4.0, 4.0, 20.0, 20.0)
+        );
+    }
+
+    #[test]
+    fn svg_view_box() {
+        let svg = r#"
+            <svg xmlns="http://www.w3.org/2000/svg" width="48" height="24" viewBox="0 -2, 32 16">
+                <rect x="4" y="0" width="24" height="12"/>
+            </svg>"#
+            .to_string();
+
+        let result = simplify_svg(svg, &Options::default()).expect("failed to simplify SVG");
+
+        assert_eq!(result.view_box, Rect::new(0.0, -2.0, 32.0, 14.0));
+    }
+
+    #[test]
+    fn svg_paints() {
+        let svg = r##"
+            <svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" color="green">
+                <rect width="8" height="8" fill="currentColor"/>
+                <g opacity="0.5"><rect x="8" width="8" height="8" fill="#ff0000"/></g>
+                <rect x="16" width="8" height="8" fill="none" stroke="CurrentColor"/>
+            </svg>"##
+            .to_string();
+
+        let options = Options {
+            color_format: Some(ColorFormat::ColrV0),
+            ..Options::default()
+        };
+        let result = simplify_svg(svg, &options).expect("failed to simplify SVG");
+
+        assert_eq!(
+            result.paints,
+            [
+                Paint::FOREGROUND,
+                Paint::Solid(Color {
+                    red: 0xFF,
+                    green: 0,
+                    blue: 0,
+                    alpha: 0x80,
+                }),
+                Paint::FOREGROUND,
+            ]
+        );
+    }
+
+    #[test]
+    fn svg_current_color_marker() {
+        // The preferred marker color, IDs and comments must not be taken for `currentColor`
+        let svg = r##"
+            <svg xmlns="http://www.w3.org/2000/svg" width="24" height="24">
+                <!-- fill="currentColor" -->
+                <rect id="currentColor" width="8" height="8" fill="#01ccfe"/>
+                <rect x="8" width="8" height="8" style="opacity: 0.5; fill:CURRENTCOLOR"/>
+                <rect x="16" width="8" height="8" fill="#ff0000"/>
+            </svg>"##
+            .to_string();
+
+        let options = Options {
+            color_format: Some(ColorFormat::ColrV1),
+            ..Options::default()
+        };
+        let result = simplify_svg(svg, &options).expect("failed to simplify SVG");
+
+        assert_eq!(
+            result.paints,
+            [
+                Paint::Solid(CURRENT_COLOR),
+                Paint::Foreground { alpha: 0x80 },
+                Paint::Solid(Color {
+                    red: 0xFF,
+                    green: 0,
+                    blue: 0,
+                    alpha: 0xFF,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn svg_default_black() {
+        let black = r##"
+            <svg xmlns="http://www.w3.org/2000/svg" width="24" height="24">
+                <rect width="8" height="8"/>
+                <rect x="8" width="8" height="8" fill="currentColor"/>
+            </svg>"##
+            .to_string();
+        let black_and_red = r##"
+            <svg xmlns="http://www.w3.org/2000/svg" width="24" height="24">
+                <rect width="8" height="8"/>
+                <rect x="8" width="8" height="8" fill="#ff0000"/>
+            </svg>"##
+            .to_string();
+
+        let options = Options {
+            color_format: Some(ColorFormat::ColrV1),
+            ..Options::default()
+        };
+        let black = simplify_svg(black, &options).expect("failed to simplify SVG");
+        let black_and_red = simplify_svg(black_and_red, &options).expect("failed to simplify SVG");
+
+        // Black is only kept as a color next to other colors
+        assert_eq!(black.paints, [Paint::FOREGROUND, Paint::FOREGROUND]);
+        assert_eq!(black_and_red.paints[0], Paint::Solid(BLACK));
+    }
+
+    #[test]
+    fn svg_gradients() {
+        let svg = r##"
+            <svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" color="#00ff00">
+                <linearGradient id="fade" x1="0" y1="0" x2="1" y2="0">
+                    <stop offset="0" stop-color="currentColor"/>
+                    <stop offset="1" stop-color="#0000ff" stop-opacity="0.5"/>
+                </linearGradient>
+                <rect x="4" y="4" width="16" height="8" fill="url(#fade)"/>
+                <rect x="4" y="12" width="16" height="8" fill="currentColor" opacity="0.5"/>
+            </svg>"##
+            .to_string();
+
+        let options = Options {
+            color_format: Some(ColorFormat::ColrV1),
+            ..Options::default()
+        };
+        let result = simplify_svg(svg, &options).expect("failed to simplify SVG");
+
+        let Paint::Gradient(gradient) = &result.paints[0] else {
+            panic!("expected gradient");
+        };
+        let GradientKind::Linear { start, end } = gradient.kind else {
+            panic!("expected linear gradient");
+        };
+        // Units of the bounding box are resolved to the coordinates of the path
+        assert_eq!(gradient.transform * start, Point::new(4.0, 4.0));
+        assert_eq!(gradient.transform * end, Point::new(20.0, 4.0));
+        // The stop with `currentColor` follows the color of the text
+        assert!(gradient.stops[0].foreground);
+        assert_eq!(gradient.stops[0].color.alpha, 0xFF);
+        assert_eq!(
+            gradient.stops[1],
+            ColorStop {
+                offset: 1.0,
+                color: Color {
+                    red: 0,
+                    green: 0,
+                    blue: 0xFF,
+                    alpha: 0x80,
+                },
+                foreground: false,
+            }
+        );
+        assert_eq!(result.paints[1], Paint::Foreground { alpha: 0x80 });
+    }
+
+    #[test]
+    fn svg_focal_radius() {
+        // Gradients with bounding box units that are shared by several paths are copied by usvg
+        let svg = r##"
+            <svg xmlns="http://www.w3.org/2000/svg" width="24" height="24">
+                <radialGradient id="glow" fr="0.25">
+                    <stop offset="0" stop-color="#ff0000"/>
+                    <stop offset="1" stop-color="#0000ff"/>
+                </radialGradient>
+                <radialGradient id="wide-glow" href="#glow" r="0.75"/>
+                <radialGradient id="user-glow" gradientUnits="userSpaceOnUse" fr="10%">
+                    <stop offset="0" stop-color="#ff0000"/>
+                    <stop offset="1" stop-color="#0000ff"/>
+                </radialGradient>
+                <rect width="8" height="8" fill="url(#glow)"/>
+                <rect x="8" width="8" height="8" fill="url(#glow)"/>
+                <rect x="16" width="8" height="8" fill="url(#wide-glow)"/>
+                <rect y="8" width="8" height="8" fill="url(#user-glow)"/>
+            </svg>"##
+            .to_string();
+
+        let options = Options {
+            color_format: Some(ColorFormat::ColrV1),
+            ..Options::default()
+        };
+        let result = simplify_svg(svg, &options).expect("failed to simplify SVG");
+
+        let start_radii: Vec<f64> = result
+            .paints
+            .iter()
+            .map(|paint| match paint {
+                Paint::Gradient(Gradient {
+                    kind: GradientKind::Radial { start_radius, .. },
+                    ..
+                }) => *start_radius,
+                _ => panic!("expected radial gradient"),
+            })
+            .collect();
+        assert_eq!(start_radii, [0.25, 0.25, 0.25, 2.4]);
+    }
 }
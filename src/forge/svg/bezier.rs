@@ -2,9 +2,8 @@ use kurbo::{
     BezPath, CubicBez, Dashes, Join, PathSeg, Point, Rect, Shape, Stroke, StrokeOptLevel,
     StrokeOpts,
 };
-use usvg::{tiny_skia_path::PathSegment, Path};
-
-const ACCURACY: f64 = 0.01;
+use std::iter;
+use usvg::{tiny_skia_path::PathSegment, Opacity, Paint, Path};
 
 /// Returns the smallest rectangle that encloses all Bézier paths.
 pub fn bounding_box(bez_paths: &[BezPath]) -> Rect {
@@ -15,36 +14,83 @@ pub fn bounding_box(bez_paths: &[BezPath]) -> Rect {
         .unwrap_or(Rect::ZERO)
 }
 
-/// Takes an usvg path and converts it to a Bézier curve
-/// where cubic segments have been replaced with quadratic segments.
+/// Returns the bounds of a path including its stroke if it leaves any ink.
+pub fn visible_bounds(svg_path: &Path) -> Option<Rect> {
+    if svg_path.data().is_empty() || !svg_path.is_visible() {
+        return None;
+    }
+
+    let has_fill = svg_path
+        .fill()
+        .is_some_and(|fill| is_visible_paint(fill.paint(), fill.opacity()));
+    let has_stroke = svg_path
+        .stroke()
+        .is_some_and(|stroke| is_visible_paint(stroke.paint(), stroke.opacity()));
+
+    let bounds = match (has_fill, has_stroke) {
+        (_, true) => svg_path.stroke_bounding_box(),
+        (true, false) => svg_path.bounding_box(),
+        (false, false) => return None,
+    };
+
+    Some(Rect::new(
+        bounds.left() as f64,
+        bounds.top() as f64,
+        bounds.right() as f64,
+        bounds.bottom() as f64,
+    ))
+}
+
+/// Takes an usvg path and converts it to a Bézier curve.
+/// Strokes are converted to outlines with the given tolerance in SVG user units.
+///
+/// Returns `None` for hidden paths and paths without visible fill or stroke.
 ///
 /// Assumes that the given path does not contain subpaths
-pub fn process_svg_path(svg_path: &Path) -> Option<BezPath> {
-    if svg_path.data().is_empty() {
+pub fn process_svg_path(svg_path: &Path, stroke_tolerance: f64) -> Option<BezPath> {
+    if svg_path.data().is_empty() || !svg_path.is_visible() {
         return None;
     }
 
-    let has_fill = svg_path.fill().is_some();
+    let has_fill = svg_path
+        .fill()
+        .is_some_and(|fill| is_visible_paint(fill.paint(), fill.opacity()));
+    let svg_stroke = svg_path
+        .stroke()
+        .filter(|stroke| is_visible_paint(stroke.paint(), stroke.opacity()));
 
-    if let Some(svg_stroke) = svg_path.stroke() {
-        let bez_path = svg_path.to_bez_path(false);
+    if let Some(svg_stroke) = svg_stroke {
+        let bez_path = svg_path.to_bez_path();
 
         let style = svg_stroke.to_kurbo();
-        let bez_path = stroke_bez_path(&bez_path, style, has_fill);
+        let bez_path = stroke_bez_path(&bez_path, style, has_fill, stroke_tolerance);
 
-        Some(bez_path.to_quadric_curves())
+        Some(bez_path)
     } else if has_fill {
-        let bez_path = svg_path.to_bez_path(true);
+        let bez_path = svg_path.to_bez_path();
         Some(bez_path)
     } else {
         None
     }
 }
 
+/// Checks whether a paint leaves any ink, i.e. whether it is not fully transparent.
+pub fn is_visible_paint(paint: &Paint, opacity: Opacity) -> bool {
+    if opacity.get() <= 0.0 {
+        return false;
+    }
+
+    match paint {
+        Paint::Color(_) => true,
+        Paint::LinearGradient(gradient) => gradient.stops().iter().any(|s| s.opacity().get() > 0.0),
+        Paint::RadialGradient(gradient) => gradient.stops().iter().any(|s| s.opacity().get() > 0.0),
+        Paint::Pattern(_) => true,
+    }
+}
+
 /// Strokes a path
-fn stroke_bez_path(bez_path: &BezPath, stroke: Stroke, has_fill: bool) -> BezPath {
+fn stroke_bez_path(bez_path: &BezPath, stroke: Stroke, has_fill: bool, tolerance: f64) -> BezPath {
     let options = StrokeOpts::default().opt_level(StrokeOptLevel::Optimized);
-    let tolerance = 0.01;
 
     // This function creates a single path for the stroke.
     // If the path was closed, it still creates a single path
@@ -114,75 +160,56 @@ fn separate_stroked_path(bez_path: &BezPath) -> Vec<BezPath> {
     }
 }
 
-trait IntoQuadraticCurves {
-    fn to_quadric_curves(&self) -> BezPath;
-}
-
-impl IntoQuadraticCurves for BezPath {
-    fn to_quadric_curves(&self) -> BezPath {
-        let mut result = vec![];
-        for segment in self.segments() {
-            match segment {
-                PathSeg::Cubic(cubic) => {
-                    for (_, _, quad) in cubic.to_quads(ACCURACY) {
-                        result.push(PathSeg::Quad(quad))
-                    }
-                }
-                segment => result.push(segment),
-            }
+/// Approximates a cubic Bézier curve with a quadratic spline and returns the points following
+/// the start point together with a flag that indicates whether a point is on the curve.
+///
+/// Consecutive off-curve points imply an on-curve point halfway between them, as in TrueType.
+pub fn cubic_to_quadratic_points(cubic: CubicBez, accuracy: f64) -> Vec<(Point, bool)> {
+    match cubic.approx_spline(accuracy) {
+        Some(spline) => {
+            let points = spline.points();
+            points[1..points.len() - 1]
+                .iter()
+                .map(|&point| (point, false))
+                .chain(iter::once((cubic.p3, true)))
+                .collect()
         }
-        BezPath::from_path_segments(result.into_iter())
+        // Fall back to separate quadratic curves
+        None => cubic
+            .to_quads(accuracy)
+            .flat_map(|(_, _, quad_bez)| [(quad_bez.p1, false), (quad_bez.p2, true)])
+            .collect(),
     }
 }
 
 trait IntoBezPath {
-    fn to_bez_path(&self, convert_cubic_curves: bool) -> BezPath;
+    fn to_bez_path(&self) -> BezPath;
 }
 
 impl IntoBezPath for Path {
-    fn to_bez_path(&self, convert_cubic_curves: bool) -> BezPath {
+    fn to_bez_path(&self) -> BezPath {
         let path_data = self.data();
 
         let mut bez_path = BezPath::new();
-        let mut current_point = Point::ZERO;
         // Note that kurbo distinguishes between elements and segments,
         // what usvg (tiny_skia_path) calls segments are elements in kurbo
         for segment in path_data.segments() {
             match segment {
                 PathSegment::MoveTo(point) => {
-                    current_point = (point.x as f64, point.y as f64).into();
                     bez_path.move_to((point.x as f64, point.y as f64));
                 }
                 PathSegment::LineTo(point) => {
-                    current_point = (point.x as f64, point.y as f64).into();
                     bez_path.line_to((point.x as f64, point.y as f64));
                 }
                 PathSegment::QuadTo(c1, point) => {
-                    current_point = (point.x as f64, point.y as f64).into();
                     bez_path.quad_to((c1.x as f64, c1.y as f64), (point.x as f64, point.y as f64));
                 }
                 PathSegment::CubicTo(c1, c2, point) => {
-                    if convert_cubic_curves {
-                        let cubic = CubicBez::new(
-                            current_point,
-                            (c1.x as f64, c1.y as f64).into(),
-                            (c2.x as f64, c2.y as f64).into(),
-                            (point.x as f64, point.y as f64).into(),
-                        );
-
-                        // Convert cubic Bézier curves to quadratic Bézier curves
-                        for (_, _, quad_bez) in cubic.to_quads(ACCURACY) {
-                            let control_point = quad_bez.p1;
-                            current_point = quad_bez.p2;
-                            bez_path.quad_to(control_point, current_point);
-                        }
-                    } else {
-                        bez_path.curve_to(
-                            (c1.x as f64, c1.y as f64),
-                            (c2.x as f64, c2.y as f64),
-                            (point.x as f64, point.y as f64),
-                        );
-                    }
+                    bez_path.curve_to(
+                        (c1.x as f64, c1.y as f64),
+                        (c2.x as f64, c2.y as f64),
+                        (point.x as f64, point.y as f64),
+                    );
                 }
                 PathSegment::Close => {
                     bez_path.close_path();
@@ -253,7 +280,7 @@ mod tests {
             _ => panic!("unfitting SVG"),
         };
 
-        let result = process_svg_path(svg_path).expect("processing SVG path failed");
+        let result = process_svg_path(svg_path, 0.01).expect("processing SVG path failed");
 
         let mut expected = BezPath::new();
         expected.move_to(Point::new(10.0, 10.0));
@@ -264,4 +291,21 @@ mod tests {
 
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn cubic_to_quadratic_spline() {
+        let cubic = CubicBez::new((0.0, 0.0), (0.0, 50.0), (50.0, 100.0), (100.0, 100.0));
+
+        let coarse = cubic_to_quadratic_points(cubic, 1.0);
+        let fine = cubic_to_quadratic_points(cubic, 0.01);
+
+        for points in [&coarse, &fine] {
+            // Only the end point is explicitly on the curve
+            assert_eq!(points.last(), Some(&(cubic.p3, true)));
+            assert!(points[..points.len() - 1]
+                .iter()
+                .all(|(_, on_curve)| !on_curve));
+        }
+        assert!(coarse.len() < fine.len());
+    }
 }
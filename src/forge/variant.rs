@@ -0,0 +1,127 @@
+use kurbo::{Affine, Vec2};
+
+use super::svg::SvgIcon;
+
+/// Glyph that is derived from another icon by an affine transform.
+///
+/// A variant is a separate glyph with its own ID and codepoint, e.g. `"arrow-down"` derived
+/// from `"arrow-right"` by [`VariantTransform::Rotate90`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Variant {
+    /// ID of the icon the variant is derived from
+    pub source_id: String,
+    pub id: String,
+    pub transform: VariantTransform,
+}
+
+/// Transform of an icon around the center of its view box.
+///
+/// Rotations are clockwise as seen on screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VariantTransform {
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    /// Mirrors the icon at the vertical axis.
+    FlipHorizontal,
+    /// Mirrors the icon at the horizontal axis.
+    FlipVertical,
+}
+
+impl VariantTransform {
+    /// Transforms the outlines and the view box of an icon.
+    pub(crate) fn apply(self, icon: &SvgIcon) -> SvgIcon {
+        let center = icon.view_box.center().to_vec2();
+        let affine = Affine::translate(center)
+            * Affine::new(self.coefficients())
+            * Affine::translate(-center);
+
+        let bez_paths = icon
+            .bez_paths
+            .iter()
+            .map(|bez_path| {
+                let bez_path = affine * bez_path;
+                // Mirroring reverses the direction of contours
+                match self {
+                    Self::FlipHorizontal | Self::FlipVertical => bez_path.reverse_subpaths(),
+                    Self::Rotate90 | Self::Rotate180 | Self::Rotate270 => bez_path,
+                }
+            })
+            .collect();
+
+        SvgIcon {
+            bez_paths,
+            paints: icon
+                .paints
+                .iter()
+                .map(|paint| paint.transformed(affine))
+                .collect(),
+            view_box: affine.transform_rect_bbox(icon.view_box),
+        }
+    }
+
+    /// Returns the exact coefficients of the linear part of the transform, the y axis of SVG
+    /// user units points down.
+    fn coefficients(self) -> [f64; 6] {
+        let (x, y) = match self {
+            Self::Rotate90 => (Vec2::new(0.0, 1.0), Vec2::new(-1.0, 0.0)),
+            Self::Rotate180 => (Vec2::new(-1.0, 0.0), Vec2::new(0.0, -1.0)),
+            Self::Rotate270 => (Vec2::new(0.0, -1.0), Vec2::new(1.0, 0.0)),
+            Self::FlipHorizontal => (Vec2::new(-1.0, 0.0), Vec2::new(0.0, 1.0)),
+            Self::FlipVertical => (Vec2::new(1.0, 0.0), Vec2::new(0.0, -1.0)),
+        };
+
+        [x.x, x.y, y.x, y.y, 0.0, 0.0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forge::Paint;
+    use kurbo::{BezPath, Point, Rect, Shape};
+
+    fn arrow_icon() -> SvgIcon {
+        // Arrow pointing to the right in the upper half of a wide view box
+        let mut bez_path = BezPath::new();
+        bez_path.move_to((10.0, 0.0));
+        bez_path.line_to((20.0, 5.0));
+        bez_path.line_to((10.0, 10.0));
+        bez_path.close_path();
+
+        SvgIcon {
+            bez_paths: vec![bez_path],
+            paints: vec![Paint::FOREGROUND],
+            view_box: Rect::new(0.0, 0.0, 40.0, 20.0),
+        }
+    }
+
+    #[test]
+    fn rotate_clockwise() {
+        let icon = VariantTransform::Rotate90.apply(&arrow_icon());
+
+        // Points down and lies in the right half of the rotated view box
+        assert_eq!(icon.view_box, Rect::new(10.0, -10.0, 30.0, 30.0));
+        assert_eq!(
+            icon.bez_paths[0].bounding_box(),
+            Rect::new(20.0, 0.0, 30.0, 10.0)
+        );
+        assert!(icon.bez_paths[0]
+            .elements()
+            .iter()
+            .any(|element| element.end_point() == Some(Point::new(25.0, 10.0))));
+    }
+
+    #[test]
+    fn flip_keeps_orientation() {
+        let original = arrow_icon();
+        let icon = VariantTransform::FlipHorizontal.apply(&original);
+
+        assert_eq!(icon.view_box, original.view_box);
+        assert_eq!(
+            icon.bez_paths[0].bounding_box(),
+            Rect::new(20.0, 0.0, 30.0, 10.0)
+        );
+        assert_eq!(icon.bez_paths[0].area(), original.bez_paths[0].area());
+    }
+}
@@ -1,16 +1,48 @@
 //! Font indexing functionality
 
-use font_types::GlyphId16;
+use font_types::{GlyphId16, Tag};
 use read_fonts::{
-    tables::cmap::{Cmap, CmapSubtable, PlatformId},
+    tables::cmap::{Cmap, Cmap12, Cmap4, CmapSubtable, PlatformId},
     FontRef, TableProvider,
 };
-use std::{collections::HashMap, fs, path::Path};
+use std::{collections::HashMap, fs, ops::RangeInclusive, path::Path};
 
 use crate::Error;
 
+/// Private use areas of the Basic Multilingual Plane and of the supplementary planes 15 and 16
+pub(crate) const PRIVATE_USE_AREAS: [RangeInclusive<u32>; 3] =
+    [0xE000..=0xF8FF, 0xF0000..=0xFFFFD, 0x100000..=0x10FFFD];
+
+/// Tag of the table that names codepoints of icons that share their glyph with another icon.
+///
+/// The glyph itself is named after the first of these icons in the `post` table. The table
+/// starts with the version [`ALIAS_TABLE_VERSION`] and the number of records, each record
+/// consists of a codepoint and the length of the UTF-8 encoded name followed by the name.
+pub(crate) const ALIAS_TABLE_TAG: Tag = Tag::new(b"ALIS");
+
+/// Version of the alias table format, tables of other versions are ignored when reading.
+pub(crate) const ALIAS_TABLE_VERSION: u16 = 1;
+
+/// Mapping of icon names to their codepoints.
+///
+/// Each icon has a private use codepoint, icons that are only mapped to standard Unicode
+/// codepoints use the lowest of them instead. Further standard Unicode codepoints of icons are
+/// returned by [`build_unicode_map`].
 pub type IconMap = HashMap<String, char>;
 
+/// Mapping of icon names to the standard Unicode codepoints they are mapped to in addition to
+/// their codepoint in the [`IconMap`], e.g. U+2192 for an arrow.
+///
+/// Icons without additional codepoints are left out.
+pub type UnicodeMap = HashMap<String, Vec<char>>;
+
+/// Returns `true` for codepoints of the private use areas.
+pub(crate) fn is_private_use(codepoint: char) -> bool {
+    PRIVATE_USE_AREAS
+        .iter()
+        .any(|area| area.contains(&(codepoint as u32)))
+}
+
 /// Returns a mapping of icon names to their assigned unicode values.
 ///
 /// This function reads a TrueType font file and extracts the mapping between glyph names
@@ -29,8 +61,34 @@ pub fn build_icon_map(font_path: impl AsRef<Path>) -> Result<IconMap, Error> {
 /// glyph names and their corresponding unicode values. This is useful for determining which
 /// unicode value to use for a specific icon glyph.
 pub fn build_icon_map_from_bytes(font_data: impl AsRef<[u8]>) -> Result<IconMap, Error> {
+    let (icon_map, _) = read_codepoints(font_data.as_ref())?;
+
+    Ok(icon_map)
+}
+
+/// Returns a mapping of icon names to their additional standard Unicode codepoints.
+///
+/// This function reads a TrueType font file like [`build_icon_map`].
+pub fn build_unicode_map(font_path: impl AsRef<Path>) -> Result<UnicodeMap, Error> {
+    let font_data = fs::read(font_path)?;
+
+    build_unicode_map_from_bytes(font_data)
+}
+
+/// Returns a mapping of icon names to their additional standard Unicode codepoints.
+///
+/// This function accepts the bytes of a TrueType font file like [`build_icon_map_from_bytes`].
+pub fn build_unicode_map_from_bytes(font_data: impl AsRef<[u8]>) -> Result<UnicodeMap, Error> {
+    let (_, unicode_map) = read_codepoints(font_data.as_ref())?;
+
+    Ok(unicode_map)
+}
+
+/// Reads the codepoints of all icons, the codepoint of an icon in the icon map is private use if
+/// possible and the lowest one otherwise.
+fn read_codepoints(font_data: &[u8]) -> Result<(IconMap, UnicodeMap), Error> {
     // Parse font
-    let font = FontRef::new(font_data.as_ref())?;
+    let font = FontRef::new(font_data)?;
 
     // Get the 'cmap' table which contains character to glyph mappings
     let cmap = font.cmap()?;
@@ -38,84 +96,275 @@ pub fn build_icon_map_from_bytes(font_data: impl AsRef<[u8]>) -> Result<IconMap,
     // Get the 'post' table which contains glyph names
     let post = font.post()?;
 
-    let mut icon_map = HashMap::new();
+    // Names of icons that share their glyph with another icon
+    let aliases = font
+        .table_data(ALIAS_TABLE_TAG)
+        .map(|data| read_aliases(data.as_bytes()))
+        .unwrap_or_default();
+
+    let mut icon_map = IconMap::new();
+    let mut unicode_map = UnicodeMap::new();
 
     for (unicode, glyph_id) in build_cmap_hashmap(&cmap) {
         // Get the glyph name from the post table
-        if let Some(glyph_name) = post.glyph_name(glyph_id) {
+        let glyph_name = aliases
+            .get(&unicode)
+            .map(String::as_str)
+            .or_else(|| post.glyph_name(glyph_id));
+        if let Some(glyph_name) = glyph_name {
             // Convert unicode value to char using from_u32
-            if let Some(unicode_char) = char::from_u32(unicode as u32) {
+            if let Some(unicode_char) = char::from_u32(unicode) {
                 // Store the mapping of glyph name to unicode char
-                icon_map.insert(glyph_name.to_string(), unicode_char);
+                insert_codepoint(&mut icon_map, &mut unicode_map, glyph_name, unicode_char);
             }
         }
     }
 
-    Ok(icon_map)
+    Ok((icon_map, unicode_map))
+}
+
+/// Adds a codepoint of an icon, private use codepoints take precedence over standard ones.
+fn insert_codepoint(
+    icon_map: &mut IconMap,
+    unicode_map: &mut UnicodeMap,
+    name: &str,
+    codepoint: char,
+) {
+    let Some(&current) = icon_map.get(name) else {
+        icon_map.insert(name.to_string(), codepoint);
+        return;
+    };
+
+    let precedes = |c1: char, c2: char| {
+        (is_private_use(c1), std::cmp::Reverse(c1)) > (is_private_use(c2), std::cmp::Reverse(c2))
+    };
+    let unicode_codepoint = if precedes(codepoint, current) {
+        icon_map.insert(name.to_string(), codepoint);
+        current
+    } else {
+        codepoint
+    };
+
+    let unicode_codepoints = unicode_map.entry(name.to_string()).or_default();
+    unicode_codepoints.push(unicode_codepoint);
+    unicode_codepoints.sort_unstable();
+}
+
+/// Reads the codepoints and names of the alias table, a truncated table is read up to the last
+/// complete record.
+pub(crate) fn read_aliases(data: &[u8]) -> HashMap<u32, String> {
+    let mut aliases = HashMap::new();
+    let Some((&[v0, v1, _, _], mut records)) = data.split_first_chunk::<4>() else {
+        return aliases;
+    };
+    if u16::from_be_bytes([v0, v1]) != ALIAS_TABLE_VERSION {
+        return aliases;
+    }
+
+    while let Some((&[c0, c1, c2, c3, l0, l1], rest)) = records.split_first_chunk::<6>() {
+        let name_len = u16::from_be_bytes([l0, l1]) as usize;
+        let Some(name) = rest.get(..name_len) else {
+            break;
+        };
+        let codepoint = u32::from_be_bytes([c0, c1, c2, c3]);
+        aliases.insert(codepoint, String::from_utf8_lossy(name).into_owned());
+        records = &rest[name_len..];
+    }
+
+    aliases
 }
 
-fn build_cmap_hashmap(cmap: &Cmap) -> HashMap<u16, GlyphId16> {
+fn build_cmap_hashmap(cmap: &Cmap) -> HashMap<u32, GlyphId16> {
     let mut mapping = HashMap::new();
-    let records = cmap.encoding_records();
 
-    let subtable: Option<CmapSubtable> = records
+    // Supplementary planes are only covered by format 12 subtables, so all Unicode subtables
+    // are combined
+    let subtables = cmap
+        .encoding_records()
         .iter()
-        .find(|record| {
+        .filter(|record| {
             record.platform_id() == PlatformId::new(0)
-                || (record.platform_id() == PlatformId::new(3) && record.encoding_id() == 1)
+                || (record.platform_id() == PlatformId::new(3)
+                    && matches!(record.encoding_id(), 1 | 10))
         })
-        .and_then(|record| cmap.resolve_offset(record.subtable_offset()).ok());
-
-    if let Some(CmapSubtable::Format4(cmap4)) = subtable {
-        let start_codes = cmap4.start_code();
-        let end_codes = cmap4.end_code();
-        let id_deltas = cmap4.id_delta();
-        let id_range_offsets = cmap4.id_range_offsets();
-        let glyph_id_array = cmap4.glyph_id_array();
-        let seg_count = (cmap4.seg_count_x2() / 2) as usize;
-
-        for i in 0..seg_count {
-            let start_code = start_codes[i].get();
-            let end_code = end_codes[i].get();
-            let id_delta = id_deltas[i].get();
-            let id_range_offset = id_range_offsets[i].get();
-
-            for cp in start_code..=end_code {
-                let glyph_id = if id_range_offset == 0 {
-                    // Use delta method
-                    // Safely handle the addition with wrapping
-                    let result = cp.wrapping_add(id_delta as u16);
-                    GlyphId16::new(result)
-                } else {
-                    // Use range offset method
-                    // Calculate offset more safely
-                    let range_offset_words = id_range_offset as usize / 2;
-                    let char_offset = (cp - start_code) as usize;
+        .filter_map(|record| cmap.resolve_offset(record.subtable_offset()).ok());
 
-                    // Check if we're within bounds before calculating the final offset
-                    if i >= id_range_offsets.len() {
-                        continue;
-                    }
+    for subtable in subtables {
+        match subtable {
+            CmapSubtable::Format4(cmap4) => insert_format4_mappings(&cmap4, &mut mapping),
+            CmapSubtable::Format12(cmap12) => insert_format12_mappings(&cmap12, &mut mapping),
+            _ => {}
+        }
+    }
+
+    mapping
+}
+
+fn insert_format4_mappings(cmap4: &Cmap4, mapping: &mut HashMap<u32, GlyphId16>) {
+    let start_codes = cmap4.start_code();
+    let end_codes = cmap4.end_code();
+    let id_deltas = cmap4.id_delta();
+    let id_range_offsets = cmap4.id_range_offsets();
+    let glyph_id_array = cmap4.glyph_id_array();
+    let seg_count = (cmap4.seg_count_x2() / 2) as usize;
 
-                    let array_offset = range_offset_words + char_offset;
-
-                    // Bounds check before accessing glyph_id_array
-                    if let Some(&gid) = glyph_id_array.get(array_offset) {
-                        let gid = gid.get();
-                        if gid == 0 {
-                            continue;
-                        }
-                        // Safely handle the addition with wrapping
-                        GlyphId16::new(gid.wrapping_add(id_delta as u16))
-                    } else {
+    for i in 0..seg_count {
+        let start_code = start_codes[i].get();
+        let end_code = end_codes[i].get();
+        let id_delta = id_deltas[i].get();
+        let id_range_offset = id_range_offsets[i].get();
+
+        for cp in start_code..=end_code {
+            let glyph_id = if id_range_offset == 0 {
+                // Use delta method
+                // Safely handle the addition with wrapping
+                let result = cp.wrapping_add(id_delta as u16);
+                GlyphId16::new(result)
+            } else {
+                // Use range offset method
+                // The offset is in bytes and relative to the position of the offset itself.
+                // The remaining `seg_count - i` offsets of the array lie between it and the
+                // glyph ID array, so they are subtracted to get an index into that array.
+                let range_offset_words = id_range_offset as usize / 2;
+                let char_offset = (cp - start_code) as usize;
+
+                let Some(array_offset) =
+                    (range_offset_words + char_offset).checked_sub(seg_count - i)
+                else {
+                    // Offsets that point before the glyph ID array are invalid
+                    continue;
+                };
+
+                // Bounds check before accessing glyph_id_array
+                if let Some(&gid) = glyph_id_array.get(array_offset) {
+                    let gid = gid.get();
+                    if gid == 0 {
                         continue;
                     }
-                };
+                    // Safely handle the addition with wrapping
+                    GlyphId16::new(gid.wrapping_add(id_delta as u16))
+                } else {
+                    continue;
+                }
+            };
 
-                mapping.insert(cp, glyph_id);
-            }
+            mapping.insert(cp as u32, glyph_id);
         }
     }
+}
 
-    mapping
+fn insert_format12_mappings(cmap12: &Cmap12, mapping: &mut HashMap<u32, GlyphId16>) {
+    for group in cmap12.groups() {
+        let start_code = group.start_char_code();
+        let start_glyph_id = group.start_glyph_id();
+        // Groups of untrusted fonts may span beyond the last Unicode codepoint
+        let end_code = group.end_char_code().min(char::MAX as u32);
+        if start_code > end_code {
+            continue;
+        }
+
+        for cp in start_code..=end_code {
+            let glyph_id = start_glyph_id.saturating_add(cp - start_code);
+            // Icon fonts do not use glyph IDs beyond 16 bits
+            let Ok(glyph_id) = u16::try_from(glyph_id) else {
+                break;
+            };
+            mapping.insert(cp, GlyphId16::new(glyph_id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use read_fonts::{FontData, FontRead};
+
+    /// Creates a format 4 cmap subtable with the given segments and glyph ID array
+    fn cmap4_data(segments: &[(u16, u16, i16, u16)], glyph_ids: &[u16]) -> Vec<u8> {
+        let seg_count = segments.len() as u16;
+        let mut data = vec![];
+        data.extend(4_u16.to_be_bytes());
+        data.extend((16 + 8 * seg_count + 2 * glyph_ids.len() as u16).to_be_bytes());
+        data.extend(0_u16.to_be_bytes());
+        data.extend((2 * seg_count).to_be_bytes());
+        // Search parameters are not used for decoding
+        data.extend([0; 6]);
+        for &(_, end_code, _, _) in segments {
+            data.extend(end_code.to_be_bytes());
+        }
+        data.extend(0_u16.to_be_bytes());
+        for &(start_code, ..) in segments {
+            data.extend(start_code.to_be_bytes());
+        }
+        for &(_, _, id_delta, _) in segments {
+            data.extend(id_delta.to_be_bytes());
+        }
+        for &(.., id_range_offset) in segments {
+            data.extend(id_range_offset.to_be_bytes());
+        }
+        for &glyph_id in glyph_ids {
+            data.extend(glyph_id.to_be_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn format4_range_offsets() {
+        let data = cmap4_data(
+            &[
+                // Offset of 3 words from the first of 3 offsets to the glyph ID array
+                (0xE000, 0xE002, 0, 6),
+                // Offset of 5 words from the second offset to the fourth glyph ID
+                (0xE010, 0xE010, 1, 10),
+                (0xFFFF, 0xFFFF, 1, 0),
+            ],
+            &[5, 0, 7, 2],
+        );
+        let cmap4 = Cmap4::read(FontData::new(&data)).unwrap();
+        let mut mapping = HashMap::new();
+
+        insert_format4_mappings(&cmap4, &mut mapping);
+
+        // Glyph ID 0 leaves a codepoint unmapped, the delta applies to looked up glyph IDs
+        assert_eq!(mapping.get(&0xE000), Some(&GlyphId16::new(5)));
+        assert_eq!(mapping.get(&0xE001), None);
+        assert_eq!(mapping.get(&0xE002), Some(&GlyphId16::new(7)));
+        assert_eq!(mapping.get(&0xE010), Some(&GlyphId16::new(3)));
+    }
+
+    /// Creates a format 12 cmap subtable with the given groups
+    fn cmap12_data(groups: &[(u32, u32, u32)]) -> Vec<u8> {
+        let mut data = vec![];
+        data.extend(12_u16.to_be_bytes());
+        data.extend(0_u16.to_be_bytes());
+        data.extend((16 + 12 * groups.len() as u32).to_be_bytes());
+        data.extend(0_u32.to_be_bytes());
+        data.extend((groups.len() as u32).to_be_bytes());
+        for &(start_code, end_code, start_glyph_id) in groups {
+            data.extend(start_code.to_be_bytes());
+            data.extend(end_code.to_be_bytes());
+            data.extend(start_glyph_id.to_be_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn format12_groups_are_bounded() {
+        let data = cmap12_data(&[
+            (0x10FFF0, u32::MAX, 1),
+            (0xF0000, 0xFFFFF, 0xFFF0),
+            (0xE001, 0xE000, 1),
+        ]);
+        let cmap12 = Cmap12::read(FontData::new(&data)).unwrap();
+        let mut mapping = HashMap::new();
+
+        insert_format12_mappings(&cmap12, &mut mapping);
+
+        // Groups stop at the last codepoint and the last 16-bit glyph ID,
+        // reversed groups map nothing
+        assert_eq!(mapping.len(), 32);
+        assert_eq!(mapping.get(&0x10FFFF), Some(&GlyphId16::new(16)));
+        assert_eq!(mapping.get(&0xF000F), Some(&GlyphId16::new(0xFFFF)));
+        assert_eq!(mapping.get(&0xF0010), None);
+    }
 }
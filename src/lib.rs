@@ -14,8 +14,10 @@
 //!
 //! # Features
 //!
-//! - `forge`: Enables font generation functionality ([`make_font`])
-//! - `index`: Enables icon mapping functionality ([`build_icon_map`], [`IconMap`])
+//! - `forge`: Enables font generation functionality ([`make_font`], [`make_font_with_options`]),
+//!   implies `index`
+//! - `index`: Enables icon mapping functionality ([`build_icon_map`], [`IconMap`],
+//!   [`build_unicode_map`])
 //!
 //! # Font generation
 //!
@@ -120,8 +122,11 @@ pub use crate::error::Error;
 
 #[cfg_attr(docsrs, doc(cfg(feature = "forge")))]
 #[cfg(feature = "forge")]
-pub use crate::forge::make_font;
+pub use crate::forge::{make_font, make_font_with_options};
 
 #[cfg_attr(docsrs, doc(cfg(feature = "index")))]
 #[cfg(feature = "index")]
-pub use crate::index::{build_icon_map, build_icon_map_from_bytes, IconMap};
+pub use crate::index::{
+    build_icon_map, build_icon_map_from_bytes, build_unicode_map, build_unicode_map_from_bytes,
+    IconMap, UnicodeMap,
+};